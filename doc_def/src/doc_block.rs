@@ -0,0 +1,281 @@
+use crate::document::{is_segment_line, ApiDocument, LineParseResult, ParseError, SegmentType};
+
+/// 结构化文档块的格式
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum BlockFormat {
+    Yaml,
+    Toml,
+}
+
+impl BlockFormat {
+    /// 解析代码块开始行中的语言，如 ```yaml
+    fn from_fence(line: &str) -> Option<BlockFormat> {
+        match line.trim().strip_prefix("```")? {
+            "yaml" | "yml" => Some(BlockFormat::Yaml),
+            "toml" => Some(BlockFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// 注释中的结构化文档块
+#[derive(Debug, Clone, Copy)]
+pub struct DocBlock {
+    pub format: BlockFormat,
+    /// ```yaml 或 ```toml 所在行
+    pub start_line: usize,
+    /// 结束的 ``` 所在行。没有结束行时为None
+    pub end_line: Option<usize>,
+}
+
+/// 查找注释中的所有结构化文档块。文档块只能出现在描述中，
+/// 段开始之后的代码块为段的内容，如 example 段中的 yaml 示例，不再查找
+pub fn find_doc_block_list(doc_list: &[String]) -> Vec<DocBlock> {
+    let mut result = Vec::new();
+    let mut line_index = 0;
+    while line_index < doc_list.len() {
+        if is_segment_line(&doc_list[line_index]) {
+            break;
+        }
+        let format = match BlockFormat::from_fence(&doc_list[line_index]) {
+            Some(val) => val,
+            None => {
+                line_index += 1;
+                continue;
+            }
+        };
+
+        let end_line = (line_index + 1..doc_list.len()).find(|val| doc_list[*val].trim() == "```");
+        result.push(DocBlock {
+            format,
+            start_line: line_index,
+            end_line,
+        });
+        line_index = match end_line {
+            Some(val) => val + 1,
+            None => doc_list.len(),
+        };
+    }
+
+    result
+}
+
+/// 解析注释中的结构化文档块。没有文档块时返回None，由调用者按行解析。
+/// 文档块之外的内容做为API描述，文档块中已指定描述时忽略
+pub(crate) fn parse_doc_block(doc_list: &[String]) -> Option<LineParseResult> {
+    let block_list = find_doc_block_list(doc_list);
+    let block = *block_list.first()?;
+
+    let mut error_list = Vec::new();
+    for block_item in &block_list[1..] {
+        let mut err = ParseError::new(SegmentType::Block, "a single yaml or toml block", "another block");
+        err.line_index = block_item.start_line;
+        error_list.push(err);
+    }
+
+    let end_line = match block.end_line {
+        Some(val) => val,
+        None => {
+            let mut err = ParseError::new(SegmentType::Block, "closing ```", "end of doc comment");
+            err.line_index = block.start_line;
+            error_list.push(err);
+            doc_list.len()
+        }
+    };
+
+    // 去掉代码块开始行的缩进，保留块内的相对缩进
+    let fence_line = &doc_list[block.start_line];
+    let indent = &fence_line[..fence_line.len() - fence_line.trim_start().len()];
+    let content = doc_list[block.start_line + 1..end_line]
+        .iter()
+        .map(|val| val.strip_prefix(indent).unwrap_or_else(|| val.trim_start()))
+        .collect::<Vec<&str>>()
+        .join("\n");
+
+    let mut document = match deserialize_block(block.format, &content) {
+        Ok(val) => val,
+        Err((line, message)) => {
+            let mut err = ParseError::new(
+                SegmentType::Block,
+                format!("valid {} api document", block.format),
+                message,
+            );
+            err.line_index = block.start_line + 1 + line;
+            error_list.push(err);
+            ApiDocument::default()
+        }
+    };
+    // 兼容只有一个返回值的写法，如 ReturnType: UserInfo
+    document.migrate_legacy_return();
+
+    if document.desc.is_empty() {
+        let desc_list: Vec<&str> = doc_list
+            .iter()
+            .enumerate()
+            .filter(|(line_index, _)| *line_index < block.start_line || *line_index > end_line)
+            .map(|(_, line)| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect();
+        document.desc = desc_list.join("\r\n");
+    }
+
+    // 文档块没有对应的段，出错时都定位到代码块开始行
+    let segment_line_list = [
+        SegmentType::ModuleName,
+        SegmentType::FnName,
+        SegmentType::Tags,
+        SegmentType::Deprecated,
+    ]
+    .iter()
+    .map(|val| (*val, block.start_line))
+    .collect();
+    let param_line_list = vec![block.start_line; document.param_list.len()];
+
+    Some(LineParseResult {
+        document,
+        error_list,
+        warning_list: Vec::new(),
+        segment_line_list,
+        param_line_list,
+    })
+}
+
+/// 反序列化文档块。出错时返回出错行在块内的索引以及错误信息
+fn deserialize_block(format: BlockFormat, content: &str) -> Result<ApiDocument, (usize, String)> {
+    match format {
+        BlockFormat::Yaml => serde_norway::from_str::<ApiDocument>(content).map_err(|err| {
+            let line = err.location().map(|val| val.line().saturating_sub(1)).unwrap_or(0);
+            (line, err.to_string())
+        }),
+        BlockFormat::Toml => toml::from_str::<ApiDocument>(content).map_err(|err| {
+            let line = err
+                .span()
+                .map(|val| content[..val.start].matches('\n').count())
+                .unwrap_or(0);
+            (line, err.message().to_string())
+        }),
+    }
+}
+
+impl std::fmt::Display for BlockFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BlockFormat::Yaml => "yaml",
+            BlockFormat::Toml => "toml",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::doc_block::{find_doc_block_list, BlockFormat};
+    use crate::document::{parse_statement, ParamLocation, SegmentType};
+    use crate::document::test::to_doc_list;
+
+    #[test]
+    pub fn test_parse_yaml_block() {
+        let parse_result = parse_statement(to_doc_list(
+            " 获取用户列表
+             ```yaml
+             ModuleName: User
+             Name: GetUserList
+             Path: /v1/user/{id}
+             HttpMethod: get
+             ParamList:
+               - Name: Page
+                 ParamType: int
+                 Desc: 页码, 从 1 开始
+                 Location: query
+               - Name: Id
+                 ParamType: int
+             ReturnType: Vec<UserInfo>
+             ```",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let document = &parse_result.document;
+        assert_eq!(document.module_name, "User");
+        assert_eq!(document.desc, "获取用户列表");
+        assert_eq!(document.param_list[0].desc, "页码, 从 1 开始");
+        assert_eq!(document.param_list[0].location, Some(ParamLocation::Query));
+        assert_eq!(document.response_list[0].type_name, "Vec<UserInfo>");
+        // 路径参数检查同样适用于结构化文档
+        assert_eq!(document.param_list[1].location, Some(ParamLocation::Path));
+    }
+
+    #[test]
+    pub fn test_parse_toml_block() {
+        let parse_result = parse_statement(to_doc_list(
+            " ```toml
+             ModuleName = \"User\"
+             Name = \"GetUser\"
+             Path = \"/v1/GetUser\"
+             HttpMethod = \"get\"
+             Desc = \"获取用户\"
+             [[ParamList]]
+             Name = \"Id\"
+             ParamType = \"int\"
+             Required = true
+             ```",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        assert_eq!(parse_result.document.desc, "获取用户");
+        assert!(parse_result.document.param_list[0].required);
+    }
+
+    #[test]
+    pub fn test_parse_block_error_line() {
+        let parse_result = parse_statement(to_doc_list(
+            " ```yaml
+             ModuleName: User
+             Name: GetUser
+             ParamList: 1
+             ```",
+        ));
+        assert_eq!(parse_result.error_list.len(), 1);
+        assert_eq!(parse_result.error_list[0].segment_type, SegmentType::Block);
+        assert_eq!(parse_result.error_list[0].line_index, 3);
+
+        let parse_result = parse_statement(to_doc_list(
+            " ```toml
+             Name = \"GetUser\"
+             Path = 1
+             ```",
+        ));
+        assert_eq!(parse_result.error_list[0].line_index, 2);
+
+        let doc_list = to_doc_list(
+            " ```yaml
+             Name: GetUser",
+        );
+        let block_list = find_doc_block_list(&doc_list);
+        assert_eq!(block_list[0].format, BlockFormat::Yaml);
+        assert!(block_list[0].end_line.is_none());
+        let parse_result = parse_statement(doc_list);
+        assert_eq!(parse_result.error_list[0].line_index, 0);
+    }
+
+    #[test]
+    pub fn test_yaml_fence_in_example() {
+        let doc_list = to_doc_list(
+            " 获取用户
+             module User
+             fn GetUser /v1/GetUser post
+             example response success 200 application/yaml
+                 ```yaml
+                 Name: test
+                 ```
+             return 200 application/yaml
+                 ```yaml
+                 Name: test
+                 ```",
+        );
+        assert!(find_doc_block_list(&doc_list).is_empty());
+        let parse_result = parse_statement(doc_list);
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let document = &parse_result.document;
+        assert_eq!(document.name, "GetUser");
+        assert_eq!(document.example_list[0].content, "```yaml\r\nName: test\r\n```");
+        assert_eq!(document.response_list[0].content, "```yaml\r\nName: test\r\n```");
+    }
+}
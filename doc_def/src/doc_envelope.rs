@@ -0,0 +1,345 @@
+use crate::document::{
+    append_desc, check_empty_first_line, get_latest_param, get_text, get_word, parse_param_tree_line, strip_comment,
+    ApiParam, ApiResponse, ParseError, SegmentType,
+};
+use serde::{Deserialize, Serialize};
+
+/// 信封中默认的数据字段名
+pub const DEFAULT_DATA_FIELD: &str = "Data";
+/// 在 envelope 段中使用时表示不使用信封
+pub const NO_ENVELOPE: &str = "none";
+
+/// 响应的信封。由 #[doc_envelope] 标记的项生成，API的响应会做为信封中数据字段的内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct EnvelopeDocument {
+    /// 信封名，在 envelope 段中引用
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// 信封描述
+    #[serde(rename = "Desc")]
+    pub desc: String,
+    /// 数据字段名，响应的字段或者类型会做为此字段的内容
+    #[serde(rename = "DataField")]
+    pub data_field: String,
+    /// 是否是默认信封。未使用 envelope 段的API使用默认信封
+    #[serde(rename = "IsDefault")]
+    pub is_default: bool,
+    /// 信封的字段列表
+    #[serde(rename = "FieldList")]
+    pub field_list: Vec<ApiParam>,
+}
+
+impl Default for EnvelopeDocument {
+    fn default() -> Self {
+        Self {
+            name: "".to_string(),
+            desc: "".to_string(),
+            data_field: DEFAULT_DATA_FIELD.to_string(),
+            is_default: false,
+            field_list: Vec::new(),
+        }
+    }
+}
+
+impl EnvelopeDocument {
+    /// 使用信封包装响应。响应的字段或者类型做为数据字段的内容，类型名变为 {信封名}<{类型名}>。
+    /// 错误响应以及只有内容、没有类型和字段的响应保持不变，返回是否已包装
+    pub fn wrap(&self, response: &mut ApiResponse) -> bool {
+        if !response.is_success() || (response.type_name.is_empty() && response.field_list.is_empty()) {
+            return false;
+        }
+
+        let mut field_list = self.field_list.clone();
+        if let Some(data_field) = field_list.iter_mut().find(|val| val.name == self.data_field) {
+            if !response.field_list.is_empty() {
+                data_field.children = std::mem::take(&mut response.field_list);
+            } else if !response.type_name.is_empty() {
+                data_field.param_type = response.type_name.clone();
+                data_field.children.clear();
+            }
+        }
+
+        response.type_name = if response.type_name.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}<{}>", &self.name, &response.type_name)
+        };
+        response.field_list = field_list;
+        true
+    }
+}
+
+/// 查找API使用的信封。name 为空时使用默认信封，为 none 时不使用信封
+pub fn find_envelope<'a>(name: &str, envelope_list: &'a [EnvelopeDocument]) -> Option<&'a EnvelopeDocument> {
+    match name {
+        "" => envelope_list.iter().find(|val| val.is_default),
+        NO_ENVELOPE => None,
+        _ => envelope_list.iter().find(|val| val.name == name),
+    }
+}
+
+/// 信封文档的解析结果
+pub struct EnvelopeParseResult {
+    pub envelope: EnvelopeDocument,
+    pub error_list: Vec<ParseError>,
+    pub warning_list: Vec<ParseError>,
+}
+
+/// 解析信封的文档注释。field 行之前的内容为描述，之后为信封的字段，格式与响应的 field 段相同。
+/// 字段中必须有名为 data_field 的数据字段
+pub fn parse_envelope(doc_list: &[String], data_field: &str) -> EnvelopeParseResult {
+    let mut result = EnvelopeParseResult {
+        envelope: EnvelopeDocument {
+            data_field: data_field.to_string(),
+            ..EnvelopeDocument::default()
+        },
+        error_list: Vec::new(),
+        warning_list: Vec::new(),
+    };
+    // field 行的缩进，之后的行为信封的字段
+    let mut field_indent = None;
+    let mut indent_list: Vec<usize> = Vec::new();
+    for (line_index, line) in doc_list.iter().enumerate() {
+        let warning_count = result.warning_list.len();
+        let parse_result = parse_envelope_line(&mut result, &mut field_indent, &mut indent_list, line);
+        for warning_item in result.warning_list[warning_count..].iter_mut() {
+            warning_item.line_index = line_index;
+        }
+        if let Err(mut err) = parse_result {
+            err.line_index = line_index;
+            result.error_list.push(err);
+        }
+    }
+
+    if !result.error_list.is_empty() {
+        return result;
+    }
+    let envelope = &result.envelope;
+    if !envelope.field_list.iter().any(|val| val.name == envelope.data_field) {
+        let mut err = ParseError::new(
+            SegmentType::Field,
+            format!("data field '{}' of envelope", &envelope.data_field),
+            "nothing",
+        );
+        err.line_index = doc_list.len().saturating_sub(1);
+        result.error_list.push(err);
+    }
+
+    result
+}
+
+fn parse_envelope_line(
+    result: &mut EnvelopeParseResult,
+    field_indent: &mut Option<usize>,
+    indent_list: &mut Vec<usize>,
+    line: &str,
+) -> Result<(), ParseError> {
+    let is_field = field_indent.is_some();
+    let segment_type = if is_field { SegmentType::Field } else { SegmentType::None };
+    let left_str = line.trim_start();
+    let indent = line.chars().take_while(|val| val.is_whitespace()).count();
+
+    // + 开头的行保留原样，做为描述或者最近一个字段描述的后续内容
+    if let Some(val) = left_str.strip_prefix('+') {
+        if !is_field {
+            append_desc(&mut result.envelope.desc, val);
+            return Ok(());
+        }
+        return match get_latest_param(&mut result.envelope.field_list) {
+            Some(field_item) => {
+                append_desc(&mut field_item.desc, val);
+                Ok(())
+            }
+            None => Err(ParseError::new(
+                SegmentType::Field,
+                "field line before '+' line",
+                format!("'{}'", left_str),
+            )),
+        };
+    }
+
+    let stripped_line = strip_comment(segment_type, left_str)?;
+    if stripped_line.trim().is_empty() {
+        return Ok(());
+    }
+    // 缩进比 field 行深的 field 为字段名
+    let is_keyword = match field_indent {
+        Some(val) => indent <= *val,
+        None => true,
+    };
+    if let Some(("field", val)) = get_word(&stripped_line).filter(|_| is_keyword) {
+        if is_field {
+            return Err(ParseError::new(SegmentType::Field, "a single field segment", "another field"));
+        }
+        *field_indent = Some(indent);
+        check_empty_first_line(SegmentType::Field, val, &mut result.warning_list);
+        return Ok(());
+    }
+
+    if !is_field {
+        append_desc(&mut result.envelope.desc, &get_text(&stripped_line));
+        return Ok(());
+    }
+    parse_param_tree_line(
+        SegmentType::Field,
+        &mut result.envelope.field_list,
+        indent,
+        &stripped_line,
+        indent_list,
+        &mut result.warning_list,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::doc_envelope::{find_envelope, parse_envelope, EnvelopeDocument, DEFAULT_DATA_FIELD, NO_ENVELOPE};
+    use crate::document::{parse_statement, ApiResponse, SegmentType};
+    use crate::document::test::to_doc_list;
+
+    #[test]
+    pub fn test_parse_envelope() {
+        let parse_result = parse_envelope(
+            &to_doc_list(
+                " 通用响应 # 注释
+                 field
+                     Code int 错误码，0 为成功
+                     Message string 错误信息
+                     +  第二行
+                     Data object nullable 数据
+                     Error object nullable 错误
+                         field string 出错的字段
+                         envelope string",
+            ),
+            DEFAULT_DATA_FIELD,
+        );
+        assert!(parse_result.error_list.is_empty(), "{:?}", parse_result.error_list);
+        let envelope = EnvelopeDocument {
+            name: "Result".to_string(),
+            is_default: true,
+            ..parse_result.envelope
+        };
+        assert_eq!(envelope.desc, "通用响应");
+        assert_eq!(envelope.field_list[1].desc, "错误信息\r\n  第二行");
+        assert_eq!(envelope.field_list[3].children[0].name, "field");
+        assert_eq!(envelope.field_list[3].children[1].name, "envelope");
+
+        let mut response = ApiResponse {
+            type_name: "UserInfo".to_string(),
+            ..ApiResponse::default()
+        };
+        assert!(envelope.wrap(&mut response));
+        assert_eq!(response.type_name, "Result<UserInfo>");
+        assert_eq!(response.field_list.len(), 4);
+        assert_eq!(response.field_list[2].param_type, "UserInfo");
+
+        let envelope_list = vec![envelope];
+        assert!(find_envelope("", &envelope_list).is_some());
+        assert!(find_envelope(NO_ENVELOPE, &envelope_list).is_none());
+        assert!(find_envelope("Page", &envelope_list).is_none());
+
+        // 响应的字段做为数据字段的子字段
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             return 200
+             field
+                 Name string 名字
+             return 404 用户不存在",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let mut document = parse_result.document;
+        assert!(document.apply_envelope(&envelope_list).is_ok());
+        assert_eq!(document.response_list[0].type_name, "Result");
+        assert_eq!(document.response_list[0].field_list[2].children[0].name, "Name");
+        // 错误响应不使用信封
+        assert!(document.response_list[1].type_name.is_empty());
+        assert!(document.response_list[1].field_list.is_empty());
+
+        // 只有内容的响应以及有类型的错误响应保持不变
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             return 200 text/plain
+                 +ok
+             return 404 application/json type ErrorBody",
+        ));
+        let mut document = parse_result.document;
+        assert!(document.apply_envelope(&envelope_list).is_ok());
+        assert!(document.response_list[0].type_name.is_empty());
+        assert_eq!(document.response_list[0].content, "ok");
+        assert_eq!(document.response_list[1].type_name, "ErrorBody");
+        assert!(document.response_list[1].field_list.is_empty());
+
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             envelope none
+             return type UserInfo",
+        ));
+        let mut document = parse_result.document;
+        assert!(document.apply_envelope(&envelope_list).is_ok());
+        assert_eq!(document.response_list[0].type_name, "UserInfo");
+
+        // 信封名写错时报告错误，响应保持不变
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             envelope Reslut
+             return type UserInfo",
+        ));
+        let mut document = parse_result.document;
+        let err = document.apply_envelope(&envelope_list).unwrap_err();
+        assert!(err.contains("'Reslut'"), "{}", err);
+        assert_eq!(document.response_list[0].type_name, "UserInfo");
+        // 没有默认信封时不使用信封
+        let mut document = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             return type UserInfo",
+        ))
+        .document;
+        assert!(document.apply_envelope(&[]).is_ok());
+
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             param
+                 Id int
+             envelope Page Result",
+        ));
+        assert_eq!(parse_result.error_list[0].segment_type, SegmentType::Envelope);
+        // 描述中不符合格式的 envelope 行为普通文本
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             envelope Page Result",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        assert!(parse_result.document.envelope.is_empty());
+
+        // 参数段中缩进更深的 envelope 为参数名
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             param
+                 envelope string 信封
+                 Id int",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        assert_eq!(parse_result.document.param_list.len(), 2);
+        assert!(parse_result.document.envelope.is_empty());
+
+        // 没有数据字段时编译失败
+        let parse_result = parse_envelope(
+            &to_doc_list(
+                " field
+                 Code int
+                 Data object",
+            ),
+            "Payload",
+        );
+        assert_eq!(parse_result.error_list.len(), 1);
+        assert_eq!(parse_result.error_list[0].line_index, 2);
+    }
+}
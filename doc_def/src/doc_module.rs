@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// 模块文档。由 #[api_module] 标记的mod或者 #[doc_module] 标记的项生成
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModuleDocument {
+    /// 模块名，与 ApiDocument 中的模块名对应
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// 模块的展示标题。为空时使用模块名
+    #[serde(rename = "Title")]
+    pub title: String,
+    /// 模块描述
+    #[serde(rename = "Desc")]
+    pub desc: String,
+    /// 展示顺序。越小越靠前，未指定的模块排在最后
+    #[serde(rename = "Order")]
+    pub order: Option<i32>,
+    /// 模块内API请求路径的前缀
+    #[serde(rename = "BasePath")]
+    pub base_path: String,
+}
+
+impl Default for ModuleDocument {
+    fn default() -> Self {
+        Self {
+            name: "".to_string(),
+            title: "".to_string(),
+            desc: "".to_string(),
+            order: None,
+            base_path: "".to_string(),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::document::{
+    append_desc, get_latest_param, get_word, parse_param_tree_line, strip_comment, ApiParam, ParseError, SegmentType,
+};
+
+/// 在 param 段中引用参数集合的关键字，格式为 use {参数集合名}
+pub const USE_KEYWORD: &str = "use";
+
+/// 可以复用的参数集合。由 #[doc_params] 标记的项生成，在 param 段中使用 use {参数集合名} 引用
+#[derive(Debug, Clone, Default)]
+pub struct ParamSetDocument {
+    /// 参数集合名
+    pub name: String,
+    /// 参数列表，格式与 param 段中的参数行相同
+    pub param_list: Vec<ApiParam>,
+}
+
+/// 参数集合文档的解析结果
+pub struct ParamSetParseResult {
+    pub param_list: Vec<ApiParam>,
+    pub error_list: Vec<ParseError>,
+    pub warning_list: Vec<ParseError>,
+}
+
+/// 解析参数集合的文档注释。每一行为一个参数，格式与 param 段中的参数行相同，可以嵌套
+pub fn parse_param_set(doc_list: &[String]) -> ParamSetParseResult {
+    let mut result = ParamSetParseResult {
+        param_list: Vec::new(),
+        error_list: Vec::new(),
+        warning_list: Vec::new(),
+    };
+    let mut indent_list: Vec<usize> = Vec::new();
+    for (line_index, line) in doc_list.iter().enumerate() {
+        let warning_count = result.warning_list.len();
+        let parse_result = parse_param_set_line(&mut result, &mut indent_list, line);
+        for warning_item in result.warning_list[warning_count..].iter_mut() {
+            warning_item.line_index = line_index;
+        }
+        if let Err(mut err) = parse_result {
+            err.line_index = line_index;
+            result.error_list.push(err);
+        }
+    }
+
+    result
+}
+
+fn parse_param_set_line(
+    result: &mut ParamSetParseResult,
+    indent_list: &mut Vec<usize>,
+    line: &str,
+) -> Result<(), ParseError> {
+    let left_str = line.trim_start();
+
+    // + 开头的行保留原样，做为最近一个参数描述的后续内容
+    if let Some(val) = left_str.strip_prefix('+') {
+        return match get_latest_param(&mut result.param_list) {
+            Some(param_item) => {
+                append_desc(&mut param_item.desc, val);
+                Ok(())
+            }
+            None => Err(ParseError::new(
+                SegmentType::Param,
+                "param line before '+' line",
+                format!("'{}'", left_str),
+            )),
+        };
+    }
+
+    let stripped_line = strip_comment(SegmentType::Param, left_str)?;
+    if stripped_line.trim().is_empty() {
+        return Ok(());
+    }
+    if let Some((USE_KEYWORD, _)) = get_word(&stripped_line) {
+        return Err(ParseError::new(
+            SegmentType::Param,
+            "param line in param set",
+            format!("'{}' (param set can not use other param set)", stripped_line.trim()),
+        ));
+    }
+
+    let indent = line.chars().take_while(|val| val.is_whitespace()).count();
+    parse_param_tree_line(
+        SegmentType::Param,
+        &mut result.param_list,
+        indent,
+        &stripped_line,
+        indent_list,
+        &mut result.warning_list,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::doc_params::{parse_param_set, ParamSetDocument};
+    use crate::document::{parse_statement_with_extra, ApiExtraInfo};
+    use crate::document::test::to_doc_list;
+
+    #[test]
+    pub fn test_use_param_set() {
+        let parse_result = parse_param_set(&to_doc_list(
+            "
+             Token string required 登录令牌 # 注释
+             UserId int required 用户id
+             +  第二行
+             Timestamp int required 时间戳
+             use other",
+        ));
+        assert_eq!(parse_result.error_list.len(), 1);
+        assert_eq!(parse_result.error_list[0].line_index, 5);
+        assert_eq!(parse_result.param_list.len(), 3);
+        assert_eq!(parse_result.param_list[1].desc, "用户id\r\n  第二行");
+
+        let extra_info = ApiExtraInfo {
+            param_set_list: vec![ParamSetDocument {
+                name: "auth".to_string(),
+                param_list: parse_result.param_list,
+            }],
+            ..ApiExtraInfo::default()
+        };
+        let parse_result = parse_statement_with_extra(
+            to_doc_list(
+                " module User
+                 fn GetUser /v1/GetUser post
+                 param
+                     Id int required 用户id
+                     use auth
+                         Page int 页码
+                     Size int 每页数量",
+            ),
+            extra_info.clone(),
+        );
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let param_list = &parse_result.document.param_list;
+        assert_eq!(param_list.len(), 6);
+        assert_eq!(param_list[1].name, "Token");
+        // use 之后缩进更深的行不会做为参数集合中参数的子参数
+        assert_eq!(param_list[4].name, "Page");
+        assert_eq!(parse_result.param_line_list, vec![3, 4, 4, 4, 5, 6]);
+        assert!(parse_result.warning_list.is_empty());
+
+        let parse_result = parse_statement_with_extra(
+            to_doc_list(
+                " module User
+                 fn GetUser /v1/GetUser post
+                 param
+                     Token string
+                     use auth
+                     use admin",
+            ),
+            extra_info,
+        );
+        // 与已有参数重名时报错，参数集合中的参数都不加入
+        assert_eq!(parse_result.error_list.len(), 2);
+        assert_eq!(parse_result.error_list[0].line_index, 4);
+        assert!(parse_result.error_list[0].to_string().contains("Token"));
+        assert_eq!(parse_result.document.param_list.len(), 1);
+        assert_eq!(parse_result.error_list[1].line_index, 5);
+        assert!(parse_result.error_list[1].to_string().contains("admin"));
+    }
+}
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+
+/// 类型文档。由 #[doc_type] 标记的struct生成
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TypeDocument {
+    /// 类型名
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// 类型所在的模块路径，如 model::user 。由源文件相对于crate根文件的位置得到，crate根模块为空
+    #[serde(rename = "ModulePath", default)]
+    pub module_path: String,
+    /// 类型描述
+    #[serde(rename = "Desc")]
+    pub desc: String,
+    /// 字段列表
+    #[serde(rename = "FieldList")]
+    pub field_list: Vec<TypeField>,
+}
+
+/// 类型的字段
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TypeField {
+    /// 字段名。如果使用了 #[serde(rename)] 或 #[serde(rename_all)] 则为重命名后的名字
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// 字段的rust类型
+    #[serde(rename = "FieldType")]
+    pub field_type: String,
+    /// 是否是Option
+    #[serde(rename = "Optional")]
+    pub optional: bool,
+    /// 字段描述
+    #[serde(rename = "Desc")]
+    pub desc: String,
+}
+
+impl Default for TypeDocument {
+    fn default() -> Self {
+        Self {
+            name: "".to_string(),
+            module_path: "".to_string(),
+            desc: "".to_string(),
+            field_list: Vec::new(),
+        }
+    }
+}
+
+impl TypeDocument {
+    /// 带模块路径的类型名，如 model::user::UserInfo
+    pub fn full_name(&self) -> String {
+        if self.module_path.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}::{}", &self.module_path, &self.name)
+        }
+    }
+}
+
+/// 类型引用。由 Vec<UserInfo>、Option<UserInfo> 等类型名解析得到
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TypeRef {
+    /// 去掉包装后的类型名，如 UserInfo
+    pub name: String,
+    /// 类型名的路径前缀，如 crate::model::UserInfo 中的 crate::model 。没有路径时为空
+    pub path: String,
+    /// 是否是列表
+    pub is_array: bool,
+    /// 是否可为空
+    pub optional: bool,
+}
+
+/// 列表类的包装类型
+const ARRAY_WRAPPER: [&str; 5] = ["Vec", "VecDeque", "LinkedList", "HashSet", "BTreeSet"];
+/// 对序列化结果透明的包装类型
+const TRANSPARENT_WRAPPER: [&str; 5] = ["Box", "Rc", "Arc", "Cow", "Json"];
+/// 字典类的包装类型，取值类型作为文档类型
+const MAP_WRAPPER: [&str; 2] = ["HashMap", "BTreeMap"];
+
+/// 解析类型引用，去掉 Vec<>、Option<>、Box<> 等包装以找到实际的文档类型名
+pub fn parse_type_ref(type_name: &str) -> TypeRef {
+    let mut result = TypeRef {
+        name: String::new(),
+        path: String::new(),
+        is_array: false,
+        optional: false,
+    };
+
+    let mut type_name = type_name.trim();
+    loop {
+        type_name = type_name.trim_start_matches('&').trim();
+        if let Some(val) = type_name.strip_prefix("'") {
+            // 去掉生命周期
+            type_name = val
+                .split_once(|c: char| c.is_whitespace())
+                .map(|val| val.1.trim())
+                .unwrap_or("");
+            continue;
+        }
+        if let Some(val) = type_name.strip_prefix("mut ") {
+            type_name = val.trim();
+            continue;
+        }
+
+        // [UserInfo] 与 [UserInfo; 3]
+        if type_name.starts_with('[') && type_name.ends_with(']') {
+            result.is_array = true;
+            let inner = &type_name[1..type_name.len() - 1];
+            type_name = match inner.rsplit_once(';') {
+                Some(val) => val.0,
+                None => inner,
+            };
+            continue;
+        }
+        // UserInfo[]
+        if let Some(val) = type_name.strip_suffix("[]") {
+            result.is_array = true;
+            type_name = val;
+            continue;
+        }
+
+        let (wrapper, arg_list) = match split_generic(type_name) {
+            Some(val) => val,
+            None => break,
+        };
+        if wrapper == "Option" && arg_list.len() == 1 {
+            result.optional = true;
+            type_name = arg_list[0];
+        } else if ARRAY_WRAPPER.contains(&wrapper) && arg_list.len() == 1 {
+            result.is_array = true;
+            type_name = arg_list[0];
+        } else if TRANSPARENT_WRAPPER.contains(&wrapper) && !arg_list.is_empty() {
+            type_name = arg_list[arg_list.len() - 1];
+        } else if MAP_WRAPPER.contains(&wrapper) && arg_list.len() == 2 {
+            type_name = arg_list[1];
+        } else {
+            // 其他的泛型类型使用其本身的类型名
+            type_name = &type_name[..type_name.find('<').unwrap_or(type_name.len())];
+            break;
+        }
+    }
+
+    // 去掉路径前缀，如 crate::model::UserInfo
+    let type_name = type_name.trim();
+    match type_name.rsplit_once("::") {
+        Some(val) => {
+            result.path = val.0.trim().to_string();
+            result.name = val.1.trim().to_string();
+        }
+        None => result.name = type_name.to_string(),
+    }
+
+    result
+}
+
+/// 基础类型的分类，用于比较文档中的类型与rust类型
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum PrimitiveKind {
+    Integer,
+    Float,
+    /// 文档中的 number ，可以是整数或浮点数
+    Number,
+    String,
+    Bool,
+}
+
+impl PrimitiveKind {
+    /// 两种基础类型是否兼容
+    pub fn is_compatible(self, other: PrimitiveKind) -> bool {
+        match (self, other) {
+            (PrimitiveKind::Number, PrimitiveKind::Integer)
+            | (PrimitiveKind::Number, PrimitiveKind::Float)
+            | (PrimitiveKind::Integer, PrimitiveKind::Number)
+            | (PrimitiveKind::Float, PrimitiveKind::Number) => true,
+            _ => self == other,
+        }
+    }
+}
+
+/// 获取基础类型的分类。文档中的类型名与rust类型名都可以识别，不是基础类型时返回None
+pub fn get_primitive_kind(type_name: &str) -> Option<PrimitiveKind> {
+    match type_name.to_lowercase().as_str() {
+        "int" | "integer" | "long" | "short" | "int32" | "int64" | "uint" | "uint32" | "uint64"
+        | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+        | "u128" | "usize" => Some(PrimitiveKind::Integer),
+        "float" | "double" | "decimal" | "f32" | "f64" => Some(PrimitiveKind::Float),
+        "number" => Some(PrimitiveKind::Number),
+        "string" | "str" | "text" | "char" => Some(PrimitiveKind::String),
+        "bool" | "boolean" => Some(PrimitiveKind::Bool),
+        _ => None,
+    }
+}
+
+/// 是否是可以有子参数的容器类型，如 object、array、Vec<T>、T[]
+pub fn is_container_type(type_name: &str) -> bool {
+    let type_ref = parse_type_ref(type_name);
+    type_ref.is_array
+        || matches!(
+            type_ref.name.to_lowercase().as_str(),
+            "object" | "array" | "map" | "list"
+        )
+}
+
+/// 拆分泛型类型，返回去掉路径的包装类型名以及泛型参数列表
+fn split_generic(type_name: &str) -> Option<(&str, Vec<&str>)> {
+    if !type_name.ends_with('>') {
+        return None;
+    }
+    let start_index = type_name.find('<')?;
+    let wrapper = type_name[..start_index].trim();
+    let wrapper = match wrapper.rsplit_once("::") {
+        Some(val) => val.1,
+        None => wrapper,
+    };
+
+    // 按最外层的逗号拆分参数
+    let arg_str = &type_name[start_index + 1..type_name.len() - 1];
+    let mut arg_list = Vec::new();
+    let mut depth = 0;
+    let mut last_index = 0;
+    for (index, char_item) in arg_str.char_indices() {
+        match char_item {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                arg_list.push(arg_str[last_index..index].trim());
+                last_index = index + 1;
+            }
+            _ => {}
+        }
+    }
+    arg_list.push(arg_str[last_index..].trim());
+
+    Some((wrapper, arg_list))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::doc_type::{get_primitive_kind, parse_type_ref, PrimitiveKind};
+
+    #[test]
+    pub fn test_parse_type_ref() {
+        let type_ref = parse_type_ref("Vec<UserInfo>");
+        assert_eq!(type_ref.name, "UserInfo");
+        assert!(type_ref.is_array);
+
+        let type_ref = parse_type_ref("Option<Box<crate::model::UserInfo>>");
+        assert_eq!(type_ref.name, "UserInfo");
+        assert_eq!(type_ref.path, "crate::model");
+        assert!(type_ref.optional);
+        assert!(!type_ref.is_array);
+
+        let type_ref = parse_type_ref("HashMap<String,Vec<UserInfo>>");
+        assert_eq!(type_ref.name, "UserInfo");
+        assert!(type_ref.is_array);
+
+        assert_eq!(parse_type_ref("&'a [UserInfo]").name, "UserInfo");
+        assert_eq!(parse_type_ref("Page<UserInfo>").name, "Page");
+    }
+
+    #[test]
+    pub fn test_get_primitive_kind() {
+        assert_eq!(get_primitive_kind("int"), get_primitive_kind("u64"));
+        assert_eq!(get_primitive_kind("String"), Some(PrimitiveKind::String));
+        assert!(get_primitive_kind("number").unwrap().is_compatible(PrimitiveKind::Float));
+        assert!(!PrimitiveKind::Integer.is_compatible(PrimitiveKind::String));
+        assert_eq!(get_primitive_kind("UserInfo"), None);
+    }
+}
@@ -1,8 +1,16 @@
+use crate::doc_block::parse_doc_block;
+use crate::doc_envelope::{find_envelope, EnvelopeDocument, NO_ENVELOPE};
+use crate::doc_params::{ParamSetDocument, USE_KEYWORD};
+use crate::doc_type::{get_primitive_kind, is_container_type, parse_type_ref};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
 
-/// API文档
+/// API文档。未指定的字段使用默认值，以便结构化文档中只写需要的字段
 #[derive(Debug, Serialize, Deserialize,Clone)]
+#[serde(default)]
 pub struct ApiDocument {
     /// api模块名
     #[serde(rename = "ModuleName")]
@@ -13,29 +21,67 @@ pub struct ApiDocument {
     /// api 名
     #[serde(rename = "Name")]
     pub name:String,
+    /// api的标签列表。一个api可以属于多个标签
+    #[serde(rename = "TagList", default)]
+    pub tag_list: Vec<String>,
     /// API的请求路径
     #[serde(rename = "Path")]
     pub path: String,
     /// Api描述
     #[serde(rename = "Desc")]
     pub desc: String,
+    /// 多语言的Api描述，键为语言，如 en、zh。由 desc[{语言}] 指定
+    #[serde(rename = "DescMap", default)]
+    pub desc_map: BTreeMap<String, String>,
 
     /// 参数列表
     #[serde(rename = "ParamList")]
     pub param_list: Vec<ApiParam>,
+    /// 请求体。由 body 段指定，未指定时为None
+    #[serde(rename = "RequestBody", default)]
+    pub request_body: Option<ApiRequestBody>,
 
-    /// 返回值内容类型
-    #[serde(rename = "ReturnContentType")]
-    pub return_content_type: ReturnContentType,
-    /// 返回值内容
-    #[serde(rename = "ReturnContent")]
-    pub return_content: String,
-    /// 返回值描述
-    #[serde(rename = "ReturnDesc")]
-    pub return_desc: String,
+    /// 响应列表。每个 return 段为一个响应，以http状态码与内容类型区分
+    #[serde(rename = "ResponseList", default)]
+    pub response_list: Vec<ApiResponse>,
+    /// 响应使用的信封名。由 envelope {信封名} 指定，为空时使用默认信封，为 none 时不使用信封
+    #[serde(rename = "Envelope", default)]
+    pub envelope: String,
+    /// 旧版本文档中唯一的返回值，只用于读取，由 migrate_legacy_return 转换为响应
+    #[serde(flatten, skip_serializing)]
+    legacy_return: LegacyReturn,
+
+    /// 错误码列表
+    #[serde(rename = "ErrorList", default)]
+    pub error_list: Vec<ApiError>,
+
+    /// 请求头列表
+    #[serde(rename = "HeaderList", default)]
+    pub header_list: Vec<ApiParam>,
+    /// 响应头列表
+    #[serde(rename = "ResponseHeaderList", default)]
+    pub response_header_list: Vec<ApiParam>,
+
+    /// 请求与响应的示例列表
+    #[serde(rename = "ExampleList", default)]
+    pub example_list: Vec<ApiExample>,
+
+    /// 从哪个版本开始提供
+    #[serde(rename = "Since", default)]
+    pub since: String,
+    /// 是否已废弃
+    #[serde(rename = "Deprecated", default)]
+    pub deprecated: bool,
+    /// 从哪个版本开始废弃
+    #[serde(rename = "DeprecatedSince", default)]
+    pub deprecated_since: String,
+    /// 替代的API名
+    #[serde(rename = "Replacement", default)]
+    pub replacement: String,
 }
 
 #[derive(Debug, Serialize, Deserialize,Clone)]
+#[serde(default)]
 pub struct ApiParam {
     #[serde(rename = "Name")]
     pub name: String,
@@ -45,8 +91,242 @@ pub struct ApiParam {
     pub  required: bool,
     #[serde(rename = "Desc")]
     pub  desc: String,
+    /// 多语言的参数描述
+    #[serde(rename = "DescMap", default)]
+    pub desc_map: BTreeMap<String, String>,
+    /// 是否展开参数类型的字段做为参数。由 param type {TypeName} 指定
+    #[serde(rename = "Flatten", default)]
+    pub flatten: bool,
+    /// 参数的传递位置。由 in:{Location} 指定，未指定时为None
+    #[serde(rename = "Location", default)]
+    pub location: Option<ParamLocation>,
+    /// 参数的取值约束
+    #[serde(rename = "Constraints", default)]
+    pub constraints: ApiParamConstraints,
+    /// 子参数。object、array等容器类型的参数下缩进更深的参数行
+    #[serde(rename = "Children", default)]
+    pub children: Vec<ApiParam>,
+}
+
+/// 参数的取值约束。由参数行中的 key=value 指定
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ApiParamConstraints {
+    /// 默认值。由 default={值} 指定
+    #[serde(rename = "Default", default)]
+    pub default: Option<String>,
+    /// 可选值列表。由 enum=a|b|c 指定
+    #[serde(rename = "EnumList", default)]
+    pub enum_list: Vec<String>,
+    /// 最小值。由 min={值} 指定
+    #[serde(rename = "Min", default)]
+    pub min: Option<f64>,
+    /// 最大值。由 max={值} 指定
+    #[serde(rename = "Max", default)]
+    pub max: Option<f64>,
+    /// 最小长度。由 len={最小长度}..{最大长度} 指定
+    #[serde(rename = "MinLen", default)]
+    pub min_len: Option<usize>,
+    /// 最大长度。由 len={最大长度} 或 len={最小长度}..{最大长度} 指定
+    #[serde(rename = "MaxLen", default)]
+    pub max_len: Option<usize>,
+    /// 正则表达式。由 pattern={正则} 指定
+    #[serde(rename = "Pattern", default)]
+    pub pattern: Option<String>,
+}
+
+impl ApiParamConstraints {
+    /// 是否没有任何约束
+    pub fn is_empty(&self) -> bool {
+        *self == ApiParamConstraints::default()
+    }
+}
+
+/// 参数行中支持的约束名
+const CONSTRAINT_KEY_LIST: [&str; 6] = ["default", "enum", "min", "max", "len", "pattern"];
+
+/// 参数的传递位置
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize, Clone, Copy)]
+pub enum ParamLocation {
+    #[serde(rename = "path")]
+    Path,
+    #[serde(rename = "query")]
+    Query,
+    #[serde(rename = "body")]
+    Body,
+    #[serde(rename = "header")]
+    Header,
+    #[serde(rename = "form")]
+    Form,
+}
+
+impl ParamLocation {
+    /// 解析 in:{Location} 中的位置名
+    pub fn from_name(name: &str) -> Option<ParamLocation> {
+        match name {
+            "path" => Some(ParamLocation::Path),
+            "query" => Some(ParamLocation::Query),
+            "body" => Some(ParamLocation::Body),
+            "header" => Some(ParamLocation::Header),
+            "form" => Some(ParamLocation::Form),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParamLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ParamLocation::Path => "path",
+            ParamLocation::Query => "query",
+            ParamLocation::Body => "body",
+            ParamLocation::Header => "header",
+            ParamLocation::Form => "form",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// API的错误码
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiError {
+    /// 错误码
+    #[serde(rename = "Code")]
+    pub code: String,
+    /// 对应的http状态码
+    #[serde(rename = "HttpStatus")]
+    pub http_status: Option<u16>,
+    /// 错误描述
+    #[serde(rename = "Desc")]
+    pub desc: String,
+}
+
+/// 请求或响应的示例
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiExample {
+    /// 示例类型
+    #[serde(rename = "Kind")]
+    pub kind: ExampleKind,
+    /// 示例名，如 success
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// 内容类型，如 application/json
+    #[serde(rename = "ContentType")]
+    pub content_type: String,
+    /// http状态码。仅响应示例有效
+    #[serde(rename = "HttpStatus")]
+    pub http_status: Option<u16>,
+    /// 示例内容
+    #[serde(rename = "Content")]
+    pub content: String,
+}
+
+/// 示例类型
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize, Clone, Copy)]
+pub enum ExampleKind {
+    #[serde(rename = "request")]
+    Request,
+    #[serde(rename = "response")]
+    Response,
 }
 
+/// 请求体。格式为 body {content-type} [type {TypeName} | example] {description?}
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ApiRequestBody {
+    /// 内容类型，如 application/json、multipart/form-data
+    #[serde(rename = "ContentType")]
+    pub content_type: String,
+    /// 请求体的描述方式
+    #[serde(rename = "Kind")]
+    pub kind: RequestBodyKind,
+    /// 类型名。仅当描述方式为Type时有效
+    #[serde(rename = "TypeName")]
+    pub type_name: String,
+    /// 字段列表。仅当描述方式为Fields时有效，格式与参数行相同
+    #[serde(rename = "FieldList")]
+    pub field_list: Vec<ApiParam>,
+    /// 原样保留的示例内容。仅当描述方式为Example时有效
+    #[serde(rename = "Content")]
+    pub content: String,
+    /// 请求体描述
+    #[serde(rename = "Desc")]
+    pub desc: String,
+}
+
+/// 请求体的描述方式
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize, Clone, Copy, Default)]
+pub enum RequestBodyKind {
+    /// 引用 #[doc_type] 标记的类型
+    #[serde(rename = "type")]
+    Type,
+    /// 之后的行为字段列表
+    #[default]
+    #[serde(rename = "fields")]
+    Fields,
+    /// 之后的行为原样保留的示例内容
+    #[serde(rename = "example")]
+    Example,
+}
+
+/// API的响应。格式为 return {http_status?} {content-type?} [type {TypeName} | string]? {description?}，
+/// 之后的行为响应内容
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ApiResponse {
+    /// http状态码。未指定时为None
+    #[serde(rename = "HttpStatus")]
+    pub http_status: Option<u16>,
+    /// 内容类型，如 application/json
+    #[serde(rename = "ContentType")]
+    pub content_type: String,
+    /// 返回值类型名，如 UserInfo、Vec<UserInfo>。由 return type {TypeName} 指定
+    #[serde(rename = "TypeName")]
+    pub type_name: String,
+    /// 响应内容
+    #[serde(rename = "Content")]
+    pub content: String,
+    /// 响应的字段列表。由 return 段之后的 field 段指定，格式与参数行相同
+    #[serde(rename = "FieldList")]
+    pub field_list: Vec<ApiParam>,
+    /// 响应描述
+    #[serde(rename = "Desc")]
+    pub desc: String,
+    /// 多语言的响应描述
+    #[serde(rename = "DescMap")]
+    pub desc_map: BTreeMap<String, String>,
+}
+
+impl ApiResponse {
+    /// 是否是成功的响应。未指定状态码时视为成功
+    pub fn is_success(&self) -> bool {
+        match self.http_status {
+            Some(val) => (200..300).contains(&val),
+            None => true,
+        }
+    }
+}
+
+/// 旧版本文档中的返回值字段
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+struct LegacyReturn {
+    #[serde(rename = "ReturnContentType")]
+    content_type: Option<ReturnContentType>,
+    #[serde(rename = "ReturnType")]
+    type_name: String,
+    #[serde(rename = "ReturnContent")]
+    content: String,
+    #[serde(rename = "ReturnDesc")]
+    desc: String,
+    #[serde(rename = "ReturnDescMap")]
+    desc_map: BTreeMap<String, String>,
+}
+
+/// multipart/form-data 中表示上传文件的字段类型
+pub const FILE_TYPE_NAME: &str = "file";
+/// 可以包含文件字段的内容类型
+pub const MULTIPART_CONTENT_TYPE: &str = "multipart/form-data";
+
 impl Default for ApiParam {
     fn default() -> Self {
         Self {
@@ -54,6 +334,11 @@ impl Default for ApiParam {
             param_type: "".to_string(),
             required: false,
             desc: "".to_string(),
+            flatten: false,
+            location: None,
+            constraints: ApiParamConstraints::default(),
+            desc_map: BTreeMap::new(),
+            children: Vec::new(),
         }
     }
 }
@@ -64,37 +349,276 @@ impl Default for ApiDocument {
             module_name: "".to_string(),
             http_method: "".to_string(),
             name:"".to_string(),
+            tag_list: Vec::new(),
             path: "".to_string(),
             desc: "".to_string(),
+            desc_map: BTreeMap::new(),
             param_list: Vec::new(),
-            return_content_type: ReturnContentType::String,
-            return_content: "".to_string(),
-            return_desc: "".to_string(),
+            request_body: None,
+            response_list: Vec::new(),
+            envelope: "".to_string(),
+            legacy_return: LegacyReturn::default(),
+            error_list: Vec::new(),
+            header_list: Vec::new(),
+            response_header_list: Vec::new(),
+            example_list: Vec::new(),
+            since: "".to_string(),
+            deprecated: false,
+            deprecated_since: "".to_string(),
+            replacement: "".to_string(),
         }
     }
 }
 
 impl ApiDocument {
+    /// 获取文档中出现的所有描述语言
+    pub fn get_lang_list(&self) -> Vec<String> {
+        let mut result: Vec<String> = self.desc_map.keys().cloned().collect();
+        let param_iter = self
+            .param_list
+            .iter()
+            .chain(self.header_list.iter())
+            .chain(self.response_header_list.iter())
+            .chain(self.request_body.iter().flat_map(|val| val.field_list.iter()))
+            .chain(self.response_list.iter().flat_map(|val| val.field_list.iter()));
+        let key_iter = param_iter
+            .flat_map(|val| val.desc_map.keys())
+            .chain(self.response_list.iter().flat_map(|val| val.desc_map.keys()));
+        for lang in key_iter {
+            if !result.contains(lang) {
+                result.push(lang.clone());
+            }
+        }
+
+        result.sort();
+        result
+    }
+
+    /// 使用指定语言的描述替换默认描述。没有该语言的描述时保留默认描述
+    pub fn localize(&mut self, lang: &str) {
+        if let Some(val) = self.desc_map.get(lang) {
+            self.desc = val.clone();
+        }
+        for response_item in self.response_list.iter_mut() {
+            if let Some(val) = response_item.desc_map.get(lang) {
+                response_item.desc = val.clone();
+            }
+        }
+        let param_iter = self
+            .param_list
+            .iter_mut()
+            .chain(self.header_list.iter_mut())
+            .chain(self.response_header_list.iter_mut())
+            .chain(self.request_body.iter_mut().flat_map(|val| val.field_list.iter_mut()))
+            .chain(self.response_list.iter_mut().flat_map(|val| val.field_list.iter_mut()));
+        for param_item in param_iter {
+            if let Some(val) = param_item.desc_map.get(lang) {
+                param_item.desc = val.clone();
+            }
+        }
+    }
+
+    /// 把旧版本文档中的返回值转换为响应。已有响应或者没有返回值时忽略
+    pub fn migrate_legacy_return(&mut self) {
+        let legacy = std::mem::take(&mut self.legacy_return);
+        if !self.response_list.is_empty()
+            || (legacy.type_name.is_empty() && legacy.content.is_empty() && legacy.desc.is_empty())
+        {
+            return;
+        }
+
+        let mut response_obj = ApiResponse {
+            content: legacy.content,
+            desc: legacy.desc,
+            desc_map: legacy.desc_map,
+            ..ApiResponse::default()
+        };
+        // 旧版本中返回值内容以换行开始
+        if let Some(val) = response_obj.content.strip_prefix("\r\n") {
+            response_obj.content = val.to_string();
+        }
+        match legacy.content_type {
+            Some(ReturnContentType::String) => {}
+            // 最初的版本没有 ReturnType ，return type {类型名} {描述} 中的类型名保存在描述的开头
+            Some(ReturnContentType::Type) if legacy.type_name.is_empty() => {
+                let desc = response_obj.desc.trim();
+                let (type_name, desc) = match desc.split_once(char::is_whitespace) {
+                    Some(val) => (val.0, val.1.trim_start()),
+                    None => (desc, ""),
+                };
+                response_obj.type_name = type_name.to_string();
+                response_obj.desc = desc.to_string();
+            }
+            _ => response_obj.type_name = legacy.type_name,
+        }
+        self.response_list.push(response_obj);
+    }
+
+    /// 使用信封包装有类型或者字段的成功响应。未指定信封且没有默认信封时不做处理，
+    /// 指定的信封不存在时响应保持不变并返回错误，以便读取者报告信封名写错等问题。
+    /// 宏展开时信封可能还未定义，所以 doc.data 中保存的是未包装的响应，读取 doc.data 的程序需要自行调用
+    pub fn apply_envelope(&mut self, envelope_list: &[EnvelopeDocument]) -> Result<(), String> {
+        let envelope = match find_envelope(&self.envelope, envelope_list) {
+            Some(val) => val,
+            None if self.envelope.is_empty() || self.envelope == NO_ENVELOPE => return Ok(()),
+            None => return Err(format!("envelope '{}' is not defined by #[doc_envelope]", &self.envelope)),
+        };
+        for response_item in self.response_list.iter_mut() {
+            envelope.wrap(response_item);
+        }
+        Ok(())
+    }
+
     /// 解析模块信息行
-    pub fn parse_module_name(&mut self, is_first: bool, line: &str) -> Result<(), String> {
-        if is_first == false {
-            return Err("module line must just one line".to_string());
+    pub fn parse_module_name(&mut self, is_first: bool, line: &str) -> Result<(), ParseError> {
+        if !is_first {
+            return Err(ParseError::new(
+                SegmentType::ModuleName,
+                "a single module line",
+                format!("extra line '{}'", line.trim()),
+            ));
+        }
+        if line.is_empty() {
+            return Err(ParseError::new(
+                SegmentType::ModuleName,
+                "module name",
+                END_OF_LINE,
+            ));
+        }
+
+        self.module_name = get_text(line);
+        Ok(())
+    }
+
+    /// 解析 desc[{语言}] 段，做为多语言的API描述。之后的行为描述的后续内容
+    pub fn parse_lang_desc(&mut self, is_first: bool, lang: &str, line: &str) -> Result<(), ParseError> {
+        let line = line.trim_end();
+        if is_first {
+            if self.desc_map.contains_key(lang) {
+                return Err(ParseError::new(
+                    SegmentType::Desc,
+                    "unique language of api desc",
+                    format!("repeated 'desc[{}]'", lang),
+                ));
+            }
+            self.desc_map.insert(lang.to_string(), get_text(line));
+            return Ok(());
+        }
+
+        if let Some(desc) = self.desc_map.get_mut(lang) {
+            if !desc.is_empty() {
+                desc.push_str("\r\n");
+            }
+            desc.push_str(line);
+        }
+        Ok(())
+    }
+
+    /// 解析参数、请求体、请求头、返回值以及响应字段段中的 desc[{语言}] 行，做为上一项的多语言描述
+    pub fn parse_item_lang_desc(&mut self, segment_type: SegmentType, lang: &str, line: &str) -> Result<(), ParseError> {
+        let desc_map = match segment_type {
+            SegmentType::Param => get_latest_param(&mut self.param_list).map(|val| &mut val.desc_map),
+            SegmentType::Body => self
+                .request_body
+                .as_mut()
+                .and_then(|val| get_latest_param(&mut val.field_list))
+                .map(|val| &mut val.desc_map),
+            SegmentType::Header => self.header_list.last_mut().map(|val| &mut val.desc_map),
+            SegmentType::ResponseHeader => self.response_header_list.last_mut().map(|val| &mut val.desc_map),
+            SegmentType::Return => self.response_list.last_mut().map(|val| &mut val.desc_map),
+            SegmentType::Field => self
+                .response_list
+                .last_mut()
+                .and_then(|val| get_latest_param(&mut val.field_list))
+                .map(|val| &mut val.desc_map),
+            _ => None,
+        };
+        let desc_map = match desc_map {
+            Some(val) => val,
+            None => {
+                return Err(ParseError::new(
+                    segment_type,
+                    format!("{} line before 'desc[{}]'", segment_type, lang),
+                    "nothing",
+                ));
+            }
+        };
+
+        if desc_map.contains_key(lang) {
+            return Err(ParseError::new(
+                segment_type,
+                format!("unique language of {} desc", segment_type),
+                format!("repeated 'desc[{}]'", lang),
+            ));
         }
+        desc_map.insert(lang.to_string(), get_text(line));
+        Ok(())
+    }
+
+    /// 解析第一个段之前的内容，做为API描述
+    pub fn parse_desc(&mut self, line: &str) -> Result<(), ParseError> {
+        let line = line.trim_end();
         if line.is_empty() {
-            return Err("have no module name ".to_string());
+            return Ok(());
+        }
+
+        if !self.desc.is_empty() {
+            self.desc.push_str("\r\n");
+        }
+        self.desc.push_str(line);
+        Ok(())
+    }
+
+    /// 解析标签行。格式为 tags {tag1}, {tag2}
+    pub fn parse_tags(
+        &mut self,
+        is_first: bool,
+        line: &str,
+        warning_list: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
+        if !is_first {
+            return Err(ParseError::new(
+                SegmentType::Tags,
+                "a single tags line",
+                format!("extra line '{}'", line.trim()),
+            ));
+        }
+
+        let tag_list: Vec<Cow<str>> = line
+            .split([',', '，'])
+            .map(|val| unquote(val.trim()))
+            .filter(|val| !val.is_empty())
+            .collect();
+        if tag_list.is_empty() {
+            return Err(ParseError::new(SegmentType::Tags, "tag name", END_OF_LINE));
+        }
+
+        for tag_item in tag_list {
+            if self.tag_list.iter().any(|val| *val == tag_item) {
+                warning_list.push(ParseError::new(
+                    SegmentType::Tags,
+                    "unique tag",
+                    format!("repeated tag '{}'", tag_item),
+                ));
+                continue;
+            }
+            self.tag_list.push(tag_item.into_owned());
         }
 
-        self.module_name = line.trim().to_string();
         Ok(())
     }
 
     /// 解析API基本信息行
-    pub fn parse_fn_line(&mut self, is_first: bool, mut line: &str) -> Result<(), String> {
+    pub fn parse_fn_line(&mut self, is_first: bool, mut line: &str) -> Result<(), ParseError> {
         if line.is_empty() {
-            return Err("have no fn line ".to_string());
+            return Err(ParseError::new(
+                SegmentType::FnName,
+                "fn name",
+                END_OF_LINE,
+            ));
         }
 
-        if is_first == false {
+        if !is_first {
             self.desc.push_str("\r\n");
             self.desc.push_str(line);
             return Ok(());
@@ -103,237 +627,2905 @@ impl ApiDocument {
         let fn_name = get_word(line.trim_start());
         match fn_name {
             Some(val) => {
-                self.name = val.0.to_string();
+                self.name = unquote(val.0).into_owned();
                 line = val.1;
             }
             None => {
-                return Err("no found fn name".to_string());
+                return Err(ParseError::new(
+                    SegmentType::FnName,
+                    "fn name",
+                    END_OF_LINE,
+                ));
             }
         }
 
         let api_path = get_word(line.trim_start());
         match api_path {
             Some(val) => {
-                self.path = val.0.to_string();
+                self.path = unquote(val.0).into_owned();
                 line = val.1;
             }
             None => {
-                return Err("no found api path".to_string());
+                return Err(ParseError::new(
+                    SegmentType::FnName,
+                    format!("api path after fn name '{}'", &self.name),
+                    END_OF_LINE,
+                ));
             }
         }
 
-        if line.is_empty() {
-            return Err("no found fn http_method".to_string());
-        }
         let http_method = get_word(line.trim_start());
         match http_method {
             Some(val) => {
-                self.http_method = val.0.to_string();
+                self.http_method = unquote(val.0).into_owned();
                 line = val.1;
             }
             None => {
-                return Err("no found fn http_method".to_string());
+                return Err(ParseError::new(
+                    SegmentType::FnName,
+                    format!("http method after api path '{}'", &self.path),
+                    END_OF_LINE,
+                ));
             }
         }
 
-        line = line.trim_start();
-        if line.is_empty() {
+        let desc = get_text(line);
+        if desc.is_empty() {
             return Ok(());
         }
-        self.desc = line.to_string();
+        if !self.desc.is_empty() {
+            self.desc.push_str("\r\n");
+        }
+        self.desc.push_str(&desc);
 
-        return Ok(());
+        Ok(())
     }
 
-    /// 解析参数
-    pub fn parse_param(&mut self, is_first: bool, mut line: &str) -> Result<(), String> {
+    /// 解析参数。可疑但合法的内容会记录到warning_list中
+    pub fn parse_param(
+        &mut self,
+        is_first: bool,
+        line: &str,
+        warning_list: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
         if is_first {
-            // 参数解析的第一行为空
+            // 参数解析的第一行为空，或者使用 type {TypeName} 指定参数类型
+            if let Some(val) = get_word(line.trim_start()) {
+                if val.0 == "type" {
+                    let mut param_obj = ApiParam {
+                        required: true,
+                        flatten: true,
+                        ..ApiParam::default()
+                    };
+                    match get_word(val.1.trim_start()) {
+                        Some(type_val) => {
+                            param_obj.param_type = unquote(type_val.0).into_owned();
+                            let mut left_str = type_val.1.trim_start();
+                            if let Some(location_val) = get_word(left_str) {
+                                if let Some(location) = parse_location(SegmentType::Param, location_val.0)? {
+                                    param_obj.location = Some(location);
+                                    left_str = location_val.1.trim_start();
+                                }
+                            }
+                            param_obj.desc = get_text(left_str);
+                        }
+                        None => {
+                            return Err(ParseError::new(
+                                SegmentType::Param,
+                                "type name after 'param type'",
+                                END_OF_LINE,
+                            ));
+                        }
+                    }
+                    self.param_list.push(param_obj);
+                } else {
+                    warning_list.push(ParseError::new(
+                        SegmentType::Param,
+                        "nothing or 'type {TypeName}' after 'param'",
+                        format!("'{}' (ignored)", line.trim()),
+                    ));
+                }
+            }
             return Ok(());
         }
 
-        line = line.trim_start();
-        //println!("param val:{}",line);
-        let mut param_obj = ApiParam::default();
-        let param_name = get_word(line);
-        match param_name {
+        let param_obj = parse_param_line(SegmentType::Param, line, &self.param_list, warning_list)?;
+        self.param_list.push(param_obj);
+
+        Ok(())
+    }
+
+    /// 解析参数段第一行之后的参数行。缩进比上一层深时，上一层的参数为容器类型则做为其子参数，否则做为其描述的后续内容
+    pub fn parse_param_item(
+        &mut self,
+        indent: usize,
+        line: &str,
+        indent_list: &mut Vec<usize>,
+        warning_list: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
+        parse_param_tree_line(
+            SegmentType::Param,
+            &mut self.param_list,
+            indent,
+            line,
+            indent_list,
+            warning_list,
+        )
+    }
+
+    /// 使用 + 开头的行做为最近一个参数描述的后续内容
+    pub fn append_param_desc(&mut self, line: &str) -> Result<(), ParseError> {
+        match get_latest_param(&mut self.param_list) {
             Some(val) => {
-                param_obj.name = val.0.to_string();
-                line = val.1;
-            }
-            None => {
-                return Err("no found param name".to_string());
+                append_desc(&mut val.desc, line);
+                Ok(())
             }
+            None => Err(ParseError::new(
+                SegmentType::Param,
+                "param line before '+' line",
+                format!("'+{}'", line),
+            )),
         }
+    }
 
-        let param_type = get_word(line.trim_start());
-        match param_type {
+    /// 解析参数段中的 use {参数集合名} 行，将参数集合中的参数加入参数列表。与已有参数重名时报错
+    pub fn use_param_set(&mut self, line: &str, param_set_list: &[ParamSetDocument]) -> Result<(), ParseError> {
+        let name = match get_word(line.trim_start()).and_then(|val| get_word(val.1.trim_start())) {
             Some(val) => {
-                param_obj.param_type = val.0.to_string();
-                line = val.1;
+                if !val.1.trim().is_empty() {
+                    return Err(ParseError::new(
+                        SegmentType::Param,
+                        END_OF_LINE,
+                        format!("'{}'", val.1.trim()),
+                    ));
+                }
+                unquote(val.0).into_owned()
             }
             None => {
-                return Err(format!("no found param {}'s type.", param_obj.name));
+                return Err(ParseError::new(
+                    SegmentType::Param,
+                    format!("param set name after '{}'", USE_KEYWORD),
+                    END_OF_LINE,
+                ));
             }
+        };
+        let param_set = match param_set_list.iter().find(|val| val.name == name) {
+            Some(val) => val,
+            None => {
+                return Err(ParseError::new(
+                    SegmentType::Param,
+                    format!("param set '{}' defined by #[doc_params]", &name),
+                    "nothing",
+                ));
+            }
+        };
+
+        // 与已有参数重名时不加入参数集合中的任何参数
+        for param_item in param_set.param_list.iter() {
+            if self.param_list.iter().any(|val| val.name == param_item.name) {
+                return Err(ParseError::new(
+                    SegmentType::Param,
+                    "unique param name",
+                    format!("repeated param '{}' from param set '{}'", &param_item.name, &name),
+                ));
+            }
+        }
+        self.param_list.extend(param_set.param_list.iter().cloned());
+
+        Ok(())
+    }
+
+    /// 解析请求体的第一行。格式为 body {content-type} [type {TypeName} | example] {description?}
+    pub fn parse_body(&mut self, line: &str) -> Result<(), ParseError> {
+        if self.request_body.is_some() {
+            return Err(ParseError::new(SegmentType::Body, "a single body segment", "another body"));
         }
 
-        let required = get_word(line.trim_start());
-        match required {
+        let mut body_obj = ApiRequestBody::default();
+        let mut line = match get_word(line.trim_start()) {
             Some(val) => {
-                if val.0 == "required" {
-                    param_obj.required = true;
-                    line = val.1;
-                } else if val.0 == "optional" {
-                    param_obj.required = false;
-                    line = val.1;
-                }
+                body_obj.content_type = unquote(val.0).into_owned();
+                val.1.trim_start()
             }
             None => {
-                param_obj.required = false;
+                return Err(ParseError::new(SegmentType::Body, "content type after 'body'", END_OF_LINE));
             }
+        };
+        // 内容类型中必须有 /，如 application/json
+        if !body_obj.content_type.contains('/') {
+            return Err(ParseError::new(
+                SegmentType::Body,
+                "content type such as 'application/json' after 'body'",
+                format!("'{}'", &body_obj.content_type),
+            ));
         }
 
-        param_obj.desc = line.trim_start().to_string();
-        self.param_list.push(param_obj);
+        match get_word(line) {
+            Some(("type", val)) => match get_word(val.trim_start()) {
+                Some(type_val) => {
+                    body_obj.kind = RequestBodyKind::Type;
+                    body_obj.type_name = unquote(type_val.0).into_owned();
+                    line = type_val.1;
+                }
+                None => {
+                    return Err(ParseError::new(SegmentType::Body, "type name after 'body type'", END_OF_LINE));
+                }
+            },
+            Some(("example", val)) => {
+                body_obj.kind = RequestBodyKind::Example;
+                line = val;
+            }
+            _ => {}
+        }
+        body_obj.desc = get_text(line);
+        self.request_body = Some(body_obj);
 
-        return Ok(());
+        Ok(())
     }
 
-    /// 解析返回文本块
-    pub fn parse_return(&mut self, is_first: bool, line: &str) -> Result<(), String> {
-        if is_first {
-            match get_word(line.trim_start()){
-                Some(val)=>{
-                    match val.0{
-                        "string"=>{
-                            self.return_desc = val.1.trim_start() .to_string();
-                            self.return_content_type=ReturnContentType::String;
-                        },
-                        "type"=>{
-                            self.return_desc = val.1.trim_start() .to_string();
-                            self.return_content_type=ReturnContentType::Type;
-                        },
-                        _=>{
-                            self.return_desc = line.trim_start().to_string();
-                        }
+    /// 解析请求体第一行之后的行。字段列表的格式与参数行相同，可以嵌套；示例内容原样保留。
+    /// 非 multipart/form-data 的请求体中出现 file 类型的字段时记录警告
+    pub fn parse_body_item(
+        &mut self,
+        indent: usize,
+        line: &str,
+        indent_list: &mut Vec<usize>,
+        warning_list: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
+        // 第一行出错时已经报告过
+        let body_obj = match self.request_body.as_mut() {
+            Some(val) => val,
+            None => return Ok(()),
+        };
+
+        match body_obj.kind {
+            RequestBodyKind::Type => Err(ParseError::new(
+                SegmentType::Body,
+                "no field line for 'body type'",
+                format!("'{}'", line.trim()),
+            )),
+            RequestBodyKind::Example => {
+                append_desc(&mut body_obj.content, line);
+                Ok(())
+            }
+            RequestBodyKind::Fields => {
+                let field_count = get_param_count(&body_obj.field_list);
+                parse_param_tree_line(
+                    SegmentType::Body,
+                    &mut body_obj.field_list,
+                    indent,
+                    line,
+                    indent_list,
+                    warning_list,
+                )?;
+                // 追加为描述的行不是新的字段
+                if get_param_count(&body_obj.field_list) == field_count {
+                    return Ok(());
+                }
+                let is_multipart = body_obj.content_type.eq_ignore_ascii_case(MULTIPART_CONTENT_TYPE);
+                if let Some(field_item) = get_latest_param(&mut body_obj.field_list) {
+                    if !is_multipart && field_item.param_type.eq_ignore_ascii_case(FILE_TYPE_NAME) {
+                        warning_list.push(ParseError::new(
+                            SegmentType::Body,
+                            format!("{} body for file field '{}'", MULTIPART_CONTENT_TYPE, &field_item.name),
+                            format!("'{}'", &body_obj.content_type),
+                        ));
                     }
-                },
-                None=>{
-                    self.return_desc = line.trim_start().to_string();
                 }
+                Ok(())
             }
-            return Ok(());
         }
+    }
 
-        self.return_content.push_str("\r\n");
-        self.return_content.push_str(line);
+    /// 使用 + 开头的行做为请求体最近一个字段描述的后续内容，或者示例内容的一行
+    pub fn append_body_line(&mut self, line: &str) -> Result<(), ParseError> {
+        let body_obj = match self.request_body.as_mut() {
+            Some(val) => val,
+            None => return Ok(()),
+        };
+        if body_obj.kind == RequestBodyKind::Example {
+            append_desc(&mut body_obj.content, line);
+            return Ok(());
+        }
 
-        Ok(())
+        match get_latest_param(&mut body_obj.field_list) {
+            Some(val) => {
+                append_desc(&mut val.desc, line);
+                Ok(())
+            }
+            None => Err(ParseError::new(
+                SegmentType::Body,
+                "field line before '+' line",
+                format!("'+{}'", line),
+            )),
+        }
     }
-}
 
-// 指定的返回值类型
-#[derive(Debug, Serialize_repr, Deserialize_repr, Clone)]
-#[repr(u8)]
-pub enum ReturnContentType {
-    String,
-    Type,
-}
+    /// 解析请求头。格式与参数相同
+    pub fn parse_header(
+        &mut self,
+        is_first: bool,
+        line: &str,
+        warning_list: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
+        if is_first {
+            check_empty_first_line(SegmentType::Header, line, warning_list);
+            return Ok(());
+        }
 
-/// 获取一个单词
-fn get_word(val: &str) -> Option<(&str, &str)> {
-    val.split_once(|val: char| val.is_ascii_whitespace())
-}
+        let header_obj = parse_param_line(SegmentType::Header, line, &self.header_list, warning_list)?;
+        self.header_list.push(header_obj);
 
-/// 代码段类型
-#[derive(Debug, Eq, PartialOrd, PartialEq, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-enum SegmentType {
-    None,
-    ModuleName,
-    FnName,
-    Param,
-    Return,
-}
+        Ok(())
+    }
 
-/// 函数文档转换
-pub fn parse_statement(doc_list: Vec<String>) -> Result<ApiDocument, String> {
-    let mut result = ApiDocument::default();
-    let mut segment_type = SegmentType::None;
+    /// 解析响应头。格式与参数相同
+    pub fn parse_response_header(
+        &mut self,
+        is_first: bool,
+        line: &str,
+        warning_list: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
+        if is_first {
+            check_empty_first_line(SegmentType::ResponseHeader, line, warning_list);
+            return Ok(());
+        }
 
-    // 提取函数的注释
-    for line in doc_list.iter() {
-        // 查找到当前应该处理的段类型
-        let mut is_first = false;
+        let header_obj = parse_param_line(
+            SegmentType::ResponseHeader,
+            line,
+            &self.response_header_list,
+            warning_list,
+        )?;
+        self.response_header_list.push(header_obj);
 
-        let mut left_str;
-        let prefix_word;
-        match get_word(line.trim_start()){
-            Some(val)=>{
-                left_str=val.1;
-                prefix_word=val.0;
-            },
-            None=>{
-                prefix_word=line.trim_start();
-                left_str="";
-            }
-        }
+        Ok(())
+    }
 
-        //println!("source '{:?}' '{}'",prefix_word,left_str);
-        match prefix_word{
-            "module"=>{
-                left_str = left_str.trim_start();
-                is_first = true;
-                segment_type = SegmentType::ModuleName;
-                //println!("---1 '{:?}' '{}'",segment_type,left_str);
-            },
-            "fn"=>{
-                left_str = left_str.trim_start();
-                is_first = true;
-                segment_type = SegmentType::FnName;
-                //println!("---2 '{:?}' '{}'",segment_type,left_str);
-            },
-            "param"=>{
-                left_str = left_str.trim_start();
-                is_first = true;
-                segment_type = SegmentType::Param;
-                //println!("---3 '{:?}' '{}'",segment_type,left_str);
-            },
-            "return"=>{
-                left_str = left_str.trim_start();
-                is_first = true;
-                segment_type = SegmentType::Return;
-                //println!("---4 '{:?}' '{}'",segment_type,left_str);
-            },
-            _=>{
-                if line.trim_start().starts_with("+"){
-                    // 多行拼接使用+ 。之所以需要这个。是因为让使用者能保留多余的空字符以保证格式
-                    left_str = &line.trim_start()[1..];
-                }else{
-                    left_str = line.trim_start();
-                }
-                //println!("---5 '{:?}' '{}'",segment_type,left_str);
+    /// 解析返回文本块。每个 return 段为一个响应，格式为
+    /// return {http_status?} {content-type?} [type {TypeName} | string]? {description?}，之后的行为响应内容
+    pub fn parse_return(&mut self, is_first: bool, line: &str) -> Result<(), ParseError> {
+        if !is_first {
+            if let Some(response_obj) = self.response_list.last_mut() {
+                append_desc(&mut response_obj.content, line);
             }
+            return Ok(());
         }
 
-        // 按照对应段进行处理
-        //println!("--- '{:?}' '{}'",segment_type,left_str);
-        match segment_type {
-            SegmentType::ModuleName => {
-                result.parse_module_name(is_first, left_str)?;
-            }
-            SegmentType::FnName => {
-                result.parse_fn_line(is_first, left_str)?;
+        let mut response_obj = ApiResponse::default();
+        let mut line = line.trim_start();
+        if let Some(val) = get_word(line) {
+            if let Some(http_status) = parse_http_status(val.0) {
+                response_obj.http_status = Some(http_status);
+                line = val.1.trim_start();
             }
-            SegmentType::Param => {
-                result.parse_param(is_first, left_str)?;
+        }
+        // 内容类型中总是包含 / ，如 application/json
+        if let Some(val) = get_word(line) {
+            if val.0.contains('/') {
+                response_obj.content_type = unquote(val.0).into_owned();
+                line = val.1.trim_start();
             }
-            SegmentType::Return => {
-                result.parse_return(is_first, left_str)?;
+        }
+        match get_word(line) {
+            Some(("string", val)) => {
+                line = val;
             }
+            Some(("type", val)) => match get_word(val.trim_start()) {
+                Some(type_val) => {
+                    response_obj.type_name = unquote(type_val.0).into_owned();
+                    line = type_val.1;
+                }
+                None => {
+                    return Err(ParseError::new(
+                        SegmentType::Return,
+                        "type name after 'return type'",
+                        END_OF_LINE,
+                    ));
+                }
+            },
             _ => {}
         }
+        response_obj.desc = get_text(line);
+
+        if self.response_list.iter().any(|val| {
+            val.http_status == response_obj.http_status && val.content_type == response_obj.content_type
+        }) {
+            return Err(ParseError::new(
+                SegmentType::Return,
+                "unique http status and content type of response",
+                format!("repeated response '{}'", get_response_key(&response_obj)),
+            ));
+        }
+        self.response_list.push(response_obj);
+
+        Ok(())
+    }
+
+    /// 解析响应字段段的第一行。字段属于之前最近的一个 return 段
+    pub fn parse_field(&mut self, line: &str, warning_list: &mut Vec<ParseError>) -> Result<(), ParseError> {
+        if self.response_list.is_empty() {
+            return Err(ParseError::new(SegmentType::Field, "return line before 'field'", "nothing"));
+        }
+        check_empty_first_line(SegmentType::Field, line, warning_list);
+        Ok(())
+    }
+
+    /// 解析响应字段。格式为 {name} {type} {nullable?} {description}，可以嵌套
+    pub fn parse_field_item(
+        &mut self,
+        indent: usize,
+        line: &str,
+        indent_list: &mut Vec<usize>,
+        warning_list: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
+        // 第一行出错时已经报告过
+        let response_obj = match self.response_list.last_mut() {
+            Some(val) => val,
+            None => return Ok(()),
+        };
+        parse_param_tree_line(
+            SegmentType::Field,
+            &mut response_obj.field_list,
+            indent,
+            line,
+            indent_list,
+            warning_list,
+        )
     }
 
-    Ok(result)
+    /// 使用 + 开头的行做为最近一个响应字段描述的后续内容
+    pub fn append_field_desc(&mut self, line: &str) -> Result<(), ParseError> {
+        match self
+            .response_list
+            .last_mut()
+            .and_then(|val| get_latest_param(&mut val.field_list))
+        {
+            Some(val) => {
+                append_desc(&mut val.desc, line);
+                Ok(())
+            }
+            None => Err(ParseError::new(
+                SegmentType::Field,
+                "field line before '+' line",
+                format!("'+{}'", line),
+            )),
+        }
+    }
+
+    /// 解析示例。格式为 example request {name} {content-type?} 或 example response {name} {http_status?} {content-type?}，
+    /// 之后的行为示例内容
+    pub fn parse_example(
+        &mut self,
+        is_first: bool,
+        line: &str,
+        warning_list: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
+        if !is_first {
+            // 内容追加到最后一个示例
+            if let Some(example_obj) = self.example_list.last_mut() {
+                if !example_obj.content.is_empty() {
+                    example_obj.content.push_str("\r\n");
+                }
+                example_obj.content.push_str(line);
+            }
+            return Ok(());
+        }
+
+        let (kind, line) = match get_word(line.trim_start()) {
+            Some(("request", val)) => (ExampleKind::Request, val),
+            Some(("response", val)) => (ExampleKind::Response, val),
+            Some(val) => {
+                return Err(ParseError::new(
+                    SegmentType::Example,
+                    "request or response after 'example'",
+                    format!("'{}'", val.0),
+                ));
+            }
+            None => {
+                return Err(ParseError::new(
+                    SegmentType::Example,
+                    "request or response after 'example'",
+                    END_OF_LINE,
+                ));
+            }
+        };
+
+        let mut example_obj = ApiExample {
+            kind,
+            name: "".to_string(),
+            content_type: "".to_string(),
+            http_status: None,
+            content: "".to_string(),
+        };
+        let mut line = match get_word(line.trim_start()) {
+            Some(val) => {
+                example_obj.name = unquote(val.0).into_owned();
+                val.1.trim_start()
+            }
+            None => {
+                return Err(ParseError::new(
+                    SegmentType::Example,
+                    "example name",
+                    END_OF_LINE,
+                ));
+            }
+        };
+
+        if kind == ExampleKind::Response {
+            if let Some(val) = get_word(line) {
+                if let Some(http_status) = parse_http_status(val.0) {
+                    example_obj.http_status = Some(http_status);
+                    line = val.1.trim_start();
+                }
+            }
+        }
+        if let Some(val) = get_word(line) {
+            example_obj.content_type = unquote(val.0).into_owned();
+            if !val.1.trim().is_empty() {
+                warning_list.push(ParseError::new(
+                    SegmentType::Example,
+                    "end of line after content type",
+                    format!("'{}' (ignored)", val.1.trim()),
+                ));
+            }
+        }
+
+        if self
+            .example_list
+            .iter()
+            .any(|val| val.kind == kind && val.name == example_obj.name)
+        {
+            warning_list.push(ParseError::new(
+                SegmentType::Example,
+                "unique example name",
+                format!("repeated example '{}'", &example_obj.name),
+            ));
+        }
+        self.example_list.push(example_obj);
+
+        Ok(())
+    }
+
+    /// 解析废弃信息行。格式为 deprecated {since?} {replacement?}
+    pub fn parse_deprecated(
+        &mut self,
+        is_first: bool,
+        line: &str,
+        warning_list: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
+        if !is_first {
+            return Err(ParseError::new(
+                SegmentType::Deprecated,
+                "a single deprecated line",
+                format!("extra line '{}'", line.trim()),
+            ));
+        }
+
+        self.deprecated = true;
+        let mut line = line.trim_start();
+        if let Some(val) = get_word(line) {
+            if is_version(val.0) {
+                self.deprecated_since = unquote(val.0).into_owned();
+                line = val.1.trim_start();
+            }
+        }
+        if let Some(val) = get_word(line) {
+            self.replacement = unquote(val.0).into_owned();
+            line = val.1.trim_start();
+        }
+        if !line.is_empty() {
+            warning_list.push(ParseError::new(
+                SegmentType::Deprecated,
+                "end of line after replacement api",
+                format!("'{}' (ignored)", line.trim()),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// 解析信封行。格式为 envelope {信封名}，envelope none 表示不使用信封
+    pub fn parse_envelope(&mut self, is_first: bool, line: &str) -> Result<(), ParseError> {
+        if !is_first {
+            return Err(ParseError::new(
+                SegmentType::Envelope,
+                "a single envelope line",
+                format!("extra line '{}'", line.trim()),
+            ));
+        }
+
+        match get_word(line.trim_start()) {
+            Some((name, "")) => {
+                self.envelope = unquote(name).into_owned();
+                Ok(())
+            }
+            Some(val) => Err(ParseError::new(
+                SegmentType::Envelope,
+                "end of line after envelope name",
+                format!("'{}'", val.1.trim()),
+            )),
+            None => Err(ParseError::new(
+                SegmentType::Envelope,
+                "envelope name or none after 'envelope'",
+                END_OF_LINE,
+            )),
+        }
+    }
+
+    /// 解析起始版本行。格式为 since {version}
+    pub fn parse_since(&mut self, is_first: bool, line: &str) -> Result<(), ParseError> {
+        if !is_first {
+            return Err(ParseError::new(
+                SegmentType::Since,
+                "a single since line",
+                format!("extra line '{}'", line.trim()),
+            ));
+        }
+
+        match get_word(line.trim_start()) {
+            Some((version, "")) => {
+                self.since = unquote(version).into_owned();
+                Ok(())
+            }
+            Some(val) => Err(ParseError::new(
+                SegmentType::Since,
+                "end of line after version",
+                format!("'{}'", val.1.trim()),
+            )),
+            None => Err(ParseError::new(
+                SegmentType::Since,
+                "version after 'since'",
+                END_OF_LINE,
+            )),
+        }
+    }
+
+    /// 解析错误码。格式为 {code} {http_status?} {description}
+    pub fn parse_error(
+        &mut self,
+        is_first: bool,
+        line: &str,
+        warning_list: &mut Vec<ParseError>,
+    ) -> Result<(), ParseError> {
+        if is_first && line.trim().is_empty() {
+            // 错误码解析的第一行可以为空
+            return Ok(());
+        }
+
+        let mut error_obj = ApiError {
+            code: "".to_string(),
+            http_status: None,
+            desc: "".to_string(),
+        };
+        let mut line = match get_word(line.trim_start()) {
+            Some(val) => {
+                error_obj.code = unquote(val.0).into_owned();
+                val.1.trim_start()
+            }
+            None => {
+                return Err(ParseError::new(
+                    SegmentType::Error,
+                    "error code",
+                    END_OF_LINE,
+                ));
+            }
+        };
+
+        if let Some(val) = get_word(line) {
+            if let Some(http_status) = parse_http_status(val.0) {
+                error_obj.http_status = Some(http_status);
+                line = val.1.trim_start();
+            }
+        }
+        error_obj.desc = get_text(line);
+
+        if self.error_list.iter().any(|val| val.code == error_obj.code) {
+            warning_list.push(ParseError::new(
+                SegmentType::Error,
+                "unique error code",
+                format!("repeated error code '{}'", &error_obj.code),
+            ));
+        }
+        self.error_list.push(error_obj);
+
+        Ok(())
+    }
+}
+
+/// 解析参数行。格式为 {name} {type} {required|optional} {description}，参数、请求头、响应头都使用此格式
+fn parse_param_line(
+    segment_type: SegmentType,
+    mut line: &str,
+    exist_list: &[ApiParam],
+    warning_list: &mut Vec<ParseError>,
+) -> Result<ApiParam, ParseError> {
+    line = line.trim_start();
+    let mut param_obj = ApiParam {
+        // 响应字段默认不为null，可以为null的字段使用 nullable 标记
+        required: segment_type == SegmentType::Field,
+        ..ApiParam::default()
+    };
+    let param_name = get_word(line);
+    match param_name {
+        Some(val) => {
+            param_obj.name = unquote(val.0).into_owned();
+            line = val.1;
+        }
+        None => {
+            return Err(ParseError::new(
+                segment_type,
+                format!("{} name", segment_type),
+                END_OF_LINE,
+            ));
+        }
+    }
+
+    let param_type = get_word(line.trim_start());
+    match param_type {
+        Some(val) => {
+            param_obj.param_type = unquote(val.0).into_owned();
+            line = val.1;
+        }
+        None => {
+            return Err(ParseError::new(
+                segment_type,
+                format!("type of {} '{}'", segment_type, &param_obj.name),
+                END_OF_LINE,
+            ));
+        }
+    }
+
+    // 类型之后可以有 in:{Location}、required/optional 以及 key=value 形式的约束，顺序不限
+    let mut constraint_key_list = Vec::new();
+    while let Some(val) = get_word(line.trim_start()) {
+        let constraint = val
+            .0
+            .split_once('=')
+            .filter(|constraint| CONSTRAINT_KEY_LIST.contains(&constraint.0));
+        if let Some((key, value)) = constraint {
+            if constraint_key_list.contains(&key) {
+                return Err(ParseError::new(
+                    segment_type,
+                    format!("unique constraint '{}' for {} '{}'", key, segment_type, &param_obj.name),
+                    format!("repeated '{}'", val.0),
+                ));
+            }
+            constraint_key_list.push(key);
+            parse_constraint(segment_type, &mut param_obj.constraints, key, &unquote(value))?;
+            line = val.1;
+        } else if val.0 == "required" {
+            param_obj.required = true;
+            line = val.1;
+        } else if val.0 == "optional" || (segment_type == SegmentType::Field && val.0 == "nullable") {
+            param_obj.required = false;
+            line = val.1;
+        } else if segment_type == SegmentType::Param && param_obj.location.is_none() {
+            match parse_location(segment_type, val.0)? {
+                Some(location) => {
+                    param_obj.location = Some(location);
+                    line = val.1;
+                }
+                None => break,
+            }
+        } else {
+            break;
+        }
+    }
+    if let Some(val) = get_word(line.trim_start()) {
+        if is_like_keyword(val.0, "required")
+            || is_like_keyword(val.0, "optional")
+            || (segment_type == SegmentType::Field && is_like_keyword(val.0, "nullable"))
+        {
+            warning_list.push(ParseError::new(
+                segment_type,
+                format!("required or optional for {} '{}'", segment_type, &param_obj.name),
+                format!("'{}' (treated as part of the description)", val.0),
+            ));
+        }
+    }
+
+    check_constraints(segment_type, &param_obj, warning_list)?;
+
+    param_obj.desc = get_text(line);
+    if exist_list.iter().any(|val| val.name == param_obj.name) {
+        warning_list.push(ParseError::new(
+            segment_type,
+            format!("unique {} name", segment_type),
+            format!("repeated {} '{}'", segment_type, &param_obj.name),
+        ));
+    }
+
+    Ok(param_obj)
+}
+
+/// 解析一个可以嵌套的参数行，参数与请求体的字段都使用此格式。indent_list 为最近一个参数所在路径上每一层的缩进。
+/// 缩进比上一层深时，上一层的参数为容器类型则做为其子参数，否则做为其描述的后续内容
+pub(crate) fn parse_param_tree_line(
+    segment_type: SegmentType,
+    root_list: &mut Vec<ApiParam>,
+    indent: usize,
+    line: &str,
+    indent_list: &mut Vec<usize>,
+    warning_list: &mut Vec<ParseError>,
+) -> Result<(), ParseError> {
+    // 回到缩进不超过当前行的层
+    while indent_list.last().is_some_and(|val| *val > indent) {
+        indent_list.pop();
+    }
+
+    let parent_depth = match indent_list.last() {
+        None => None,
+        Some(val) if *val == indent => indent_list.len().checked_sub(2),
+        Some(_) => {
+            let depth = indent_list.len() - 1;
+            let param_item = match get_last_param(root_list, depth) {
+                Some(val) => val,
+                None => return Err(ParseError::new(segment_type, "param line", "nothing")),
+            };
+            if !is_container_type(&param_item.param_type) {
+                // 第二个单词是类型名时可能是缩进错误的参数行
+                let type_name = get_word(line.trim_start())
+                    .and_then(|val| get_word(val.1.trim_start()))
+                    .map(|val| unquote(val.0));
+                if let Some(type_name) = type_name {
+                    if get_primitive_kind(&type_name).is_some() || is_container_type(&type_name) {
+                        warning_list.push(ParseError::new(
+                            segment_type,
+                            format!("param line at the indentation of '{}'", &param_item.name),
+                            format!("'{}' (treated as description of '{}')", line.trim(), &param_item.name),
+                        ));
+                    }
+                }
+                append_desc(&mut param_item.desc, &get_text(line));
+                return Ok(());
+            }
+            Some(depth)
+        }
+    };
+
+    let param_list = match parent_depth {
+        Some(depth) => match get_last_param(root_list, depth) {
+            Some(val) => &mut val.children,
+            None => return Err(ParseError::new(segment_type, "param line", "nothing")),
+        },
+        None => root_list,
+    };
+    let param_obj = parse_param_line(segment_type, line, param_list, warning_list)?;
+    param_list.push(param_obj);
+
+    indent_list.truncate(parent_depth.map_or(0, |val| val + 1));
+    indent_list.push(indent);
+    Ok(())
+}
+
+/// 获取指定层中最后一个参数，depth为0时为最外层
+fn get_last_param(param_list: &mut [ApiParam], depth: usize) -> Option<&mut ApiParam> {
+    let param_item = param_list.last_mut()?;
+    if depth == 0 {
+        return Some(param_item);
+    }
+    get_last_param(&mut param_item.children, depth - 1)
+}
+
+/// 获取参数的总数，包括所有层的子参数
+fn get_param_count(param_list: &[ApiParam]) -> usize {
+    param_list.iter().map(|val| 1 + get_param_count(&val.children)).sum()
+}
+
+/// 获取最近解析的参数，即沿最后一个子参数找到的最深的参数
+pub(crate) fn get_latest_param(param_list: &mut [ApiParam]) -> Option<&mut ApiParam> {
+    let param_item = param_list.last_mut()?;
+    if param_item.children.is_empty() {
+        return Some(param_item);
+    }
+    get_latest_param(&mut param_item.children)
+}
+
+/// 在描述之后追加一行
+pub(crate) fn append_desc(desc: &mut String, line: &str) {
+    if !desc.is_empty() {
+        desc.push_str("\r\n");
+    }
+    desc.push_str(line);
+}
+
+/// 解析 in:{Location} 。单词不以 in: 开头时返回None
+fn parse_location(segment_type: SegmentType, word: &str) -> Result<Option<ParamLocation>, ParseError> {
+    let name = match word.strip_prefix("in:") {
+        Some(val) => val,
+        None => return Ok(None),
+    };
+
+    match ParamLocation::from_name(name) {
+        Some(val) => Ok(Some(val)),
+        None => Err(ParseError::new(
+            segment_type,
+            "one of in:path, in:query, in:body, in:header, in:form",
+            format!("'{}'", word),
+        )),
+    }
+}
+
+/// 解析一个 key=value 形式的约束
+fn parse_constraint(
+    segment_type: SegmentType,
+    constraints: &mut ApiParamConstraints,
+    key: &str,
+    value: &str,
+) -> Result<(), ParseError> {
+    let value_err = |expected: &str| {
+        ParseError::new(
+            segment_type,
+            format!("{} after '{}='", expected, key),
+            if value.is_empty() {
+                END_OF_LINE.to_string()
+            } else {
+                format!("'{}'", value)
+            },
+        )
+    };
+
+    match key {
+        "default" => constraints.default = Some(value.to_string()),
+        "enum" => {
+            let enum_list: Vec<String> = value.split('|').map(|val| val.to_string()).collect();
+            if enum_list.iter().any(|val| val.is_empty()) {
+                return Err(value_err("values separated by '|'"));
+            }
+            constraints.enum_list = enum_list;
+        }
+        "min" => constraints.min = Some(value.parse().map_err(|_| value_err("number"))?),
+        "max" => constraints.max = Some(value.parse().map_err(|_| value_err("number"))?),
+        "len" => {
+            // len=10 、len=1..10 、len=1.. 、len=..10
+            let (min_len, max_len) = match value.split_once("..") {
+                Some(val) => (val.0, val.1),
+                None => ("", value),
+            };
+            if min_len.is_empty() && max_len.is_empty() {
+                return Err(value_err("length or length range"));
+            }
+            if !min_len.is_empty() {
+                constraints.min_len = Some(min_len.parse().map_err(|_| value_err("length or length range"))?);
+            }
+            if !max_len.is_empty() {
+                constraints.max_len = Some(max_len.parse().map_err(|_| value_err("length or length range"))?);
+            }
+        }
+        "pattern" => {
+            if value.is_empty() {
+                return Err(value_err("regular expression"));
+            }
+            constraints.pattern = Some(value.to_string());
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// 检查约束之间是否矛盾
+fn check_constraints(
+    segment_type: SegmentType,
+    param_obj: &ApiParam,
+    warning_list: &mut Vec<ParseError>,
+) -> Result<(), ParseError> {
+    let constraints = &param_obj.constraints;
+    if let (Some(min), Some(max)) = (constraints.min, constraints.max) {
+        if min > max {
+            return Err(ParseError::new(
+                segment_type,
+                format!("min <= max for {} '{}'", segment_type, &param_obj.name),
+                format!("min={} max={}", min, max),
+            ));
+        }
+    }
+    if let (Some(min_len), Some(max_len)) = (constraints.min_len, constraints.max_len) {
+        if min_len > max_len {
+            return Err(ParseError::new(
+                segment_type,
+                format!("min length <= max length for {} '{}'", segment_type, &param_obj.name),
+                format!("len={}..{}", min_len, max_len),
+            ));
+        }
+    }
+    if let Some(default) = &constraints.default {
+        if !constraints.enum_list.is_empty() && !constraints.enum_list.contains(default) {
+            warning_list.push(ParseError::new(
+                segment_type,
+                format!("default value of {} '{}' in enum", segment_type, &param_obj.name),
+                format!("'{}'", default),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// 合并属性参数。文档注释中未指定时使用属性参数，两者不一致时报错
+fn merge_attribute(
+    doc_value: &mut String,
+    attribute_value: String,
+    segment_type: SegmentType,
+    attribute_name: &str,
+    error_list: &mut Vec<ParseError>,
+) {
+    if doc_value.is_empty() {
+        *doc_value = attribute_value;
+    } else if *doc_value != attribute_value {
+        error_list.push(ParseError::new(
+            segment_type,
+            format!("{} '{}' same as the api attribute", attribute_name, attribute_value),
+            format!("'{}'", doc_value),
+        ));
+    }
+}
+
+/// 在请求路径前添加前缀，如 /v1 与 /user 合并为 /v1/user
+pub fn join_base_path(base_path: &str, path: &str) -> String {
+    let base_path = base_path.trim_end_matches('/');
+    if base_path.is_empty() {
+        return path.to_string();
+    }
+
+    let path = path.trim_start_matches('/');
+    if path.is_empty() {
+        base_path.to_string()
+    } else {
+        format!("{}/{}", base_path, path)
+    }
+}
+
+/// 合并推断出的参数，返回需要追加的参数。
+/// 与手写参数同名(或同类型的 param type)时使用手写的参数，手写参数未指定位置时使用推断的位置；
+/// 手写参数中已有相同位置的参数时，不再追加该位置推断出的类型参数
+fn merge_inferred_params(param_list: &mut [ApiParam], inferred_param_list: Vec<ApiParam>) -> Vec<ApiParam> {
+    let mut result = Vec::new();
+    for inferred_item in inferred_param_list {
+        if inferred_item.flatten {
+            let type_name = parse_type_ref(&inferred_item.param_type).name;
+            let is_exist = param_list.iter_mut().any(|val| {
+                if val.flatten && parse_type_ref(&val.param_type).name == type_name {
+                    if val.location.is_none() {
+                        val.location = inferred_item.location;
+                    }
+                    return true;
+                }
+                inferred_item.location.is_some() && val.location == inferred_item.location
+            });
+            if !is_exist {
+                result.push(inferred_item);
+            }
+            continue;
+        }
+
+        let name = normalize_param_name(&inferred_item.name);
+        match param_list
+            .iter_mut()
+            .find(|val| !val.flatten && normalize_param_name(&val.name) == name)
+        {
+            Some(val) => {
+                if val.location.is_none() {
+                    val.location = inferred_item.location;
+                }
+            }
+            None => result.push(inferred_item),
+        }
+    }
+
+    result
+}
+
+/// 获取请求路径中的占位符，支持 {name} 与 :name 两种形式
+pub fn get_path_placeholder_list(path: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    for item in path.split('/') {
+        let name = if let Some(val) = item.strip_prefix('{').and_then(|val| val.strip_suffix('}')) {
+            val.trim_start_matches('*')
+        } else if let Some(val) = item.strip_prefix(':') {
+            val
+        } else {
+            continue;
+        };
+        if !name.is_empty() {
+            result.push(name.to_string());
+        }
+    }
+
+    result
+}
+
+/// 规范化参数名以便比较。如 ProjectId 与 project_id 视为同一个名字
+pub fn normalize_param_name(name: &str) -> String {
+    name.chars()
+        .filter(|val| *val != '_' && *val != '-')
+        .flat_map(|val| val.to_lowercase())
+        .collect()
+}
+
+/// 检查路径占位符与路径参数是否一致。
+/// 未指定位置且与占位符同名的参数会被视为路径参数
+fn check_path_param(
+    document: &mut ApiDocument,
+    fn_line_index: usize,
+    param_line_list: &[usize],
+    error_list: &mut Vec<ParseError>,
+) {
+    // 展开类型的路径参数无法得知其字段名，不做检查
+    if document
+        .param_list
+        .iter()
+        .any(|val| val.flatten && val.location == Some(ParamLocation::Path))
+    {
+        return;
+    }
+
+    let placeholder_list = get_path_placeholder_list(&document.path);
+    for placeholder in &placeholder_list {
+        let placeholder_name = normalize_param_name(placeholder);
+        let param_obj = document
+            .param_list
+            .iter_mut()
+            .find(|val| !val.flatten && normalize_param_name(&val.name) == placeholder_name);
+        match param_obj {
+            Some(param_obj) => match param_obj.location {
+                None => param_obj.location = Some(ParamLocation::Path),
+                Some(ParamLocation::Path) => {}
+                Some(location) => {
+                    let mut err = ParseError::new(
+                        SegmentType::FnName,
+                        format!("path param for placeholder '{}'", placeholder),
+                        format!("param '{}' is in:{}", &param_obj.name, location),
+                    );
+                    err.line_index = fn_line_index;
+                    error_list.push(err);
+                }
+            },
+            None => {
+                let mut err = ParseError::new(
+                    SegmentType::FnName,
+                    format!("path param for placeholder '{}'", placeholder),
+                    "no matching param",
+                );
+                err.line_index = fn_line_index;
+                error_list.push(err);
+            }
+        }
+    }
+
+    for (index, param_item) in document.param_list.iter().enumerate() {
+        if param_item.location != Some(ParamLocation::Path) || param_item.flatten {
+            continue;
+        }
+        let param_name = normalize_param_name(&param_item.name);
+        if placeholder_list
+            .iter()
+            .any(|val| normalize_param_name(val) == param_name)
+        {
+            continue;
+        }
+
+        let mut err = ParseError::new(
+            SegmentType::Param,
+            format!("placeholder for path param '{}' in '{}'", &param_item.name, &document.path),
+            "no matching placeholder",
+        );
+        err.line_index = param_line_list.get(index).copied().unwrap_or(fn_line_index);
+        error_list.push(err);
+    }
+}
+
+/// 检查段的第一行是否为空，不为空时内容会被忽略
+pub(crate) fn check_empty_first_line(segment_type: SegmentType, line: &str, warning_list: &mut Vec<ParseError>) {
+    if !line.trim().is_empty() {
+        warning_list.push(ParseError::new(
+            segment_type,
+            format!("nothing after '{}'", segment_type),
+            format!("'{}' (ignored)", line.trim()),
+        ));
+    }
+}
+
+/// 是否是版本号，如 1.2.0、v2
+fn is_version(word: &str) -> bool {
+    let word = word.strip_prefix(|val: char| val == 'v' || val == 'V').unwrap_or(word);
+    word.starts_with(|val: char| val.is_ascii_digit())
+}
+
+/// 获取响应的名字，用于错误信息，如 200 application/json
+fn get_response_key(response_obj: &ApiResponse) -> String {
+    let http_status = match response_obj.http_status {
+        Some(val) => val.to_string(),
+        None => "default".to_string(),
+    };
+    if response_obj.content_type.is_empty() {
+        http_status
+    } else {
+        format!("{} {}", http_status, &response_obj.content_type)
+    }
+}
+
+/// 解析http状态码。不是合法的状态码时返回None
+fn parse_http_status(word: &str) -> Option<u16> {
+    match word.parse::<u16>() {
+        Ok(val) if (100..600).contains(&val) => Some(val),
+        _ => None,
+    }
+}
+
+// 旧版本文档中指定的返回值类型
+#[derive(Debug, Serialize_repr, Deserialize_repr, Clone)]
+#[repr(u8)]
+pub enum ReturnContentType {
+    String,
+    Type,
+}
+
+/// 文档注释语法的版本，可使用 syntax {版本号} 声明。语法说明见 GRAMMAR.md
+pub const GRAMMAR_VERSION: u32 = 1;
+
+/// 引号字符串中支持的转义字符，如 \" 、\n
+const ESCAPE_CHAR_LIST: [char; 5] = ['"', '\\', 'n', 't', '#'];
+
+/// 引号能否在此处开始。引号只能出现在单词开头或者 key= 之后
+fn is_quote_start(prev: Option<char>) -> bool {
+    match prev {
+        Some(val) => val.is_whitespace() || val == '=',
+        None => true,
+    }
+}
+
+/// 获取一个单词。单词以任意Unicode空白字符分隔，引号中的空白字符属于单词。
+/// 返回原样的单词(包括引号)以及剩余的内容，没有单词时返回None
+pub(crate) fn get_word(val: &str) -> Option<(&str, &str)> {
+    if val.is_empty() {
+        return None;
+    }
+
+    let mut is_quoted = false;
+    let mut is_escaped = false;
+    let mut prev = None;
+    for (index, ch) in val.char_indices() {
+        if is_quoted {
+            if is_escaped {
+                is_escaped = false;
+            } else if ch == '\\' {
+                is_escaped = true;
+            } else if ch == '"' {
+                is_quoted = false;
+            }
+        } else if ch.is_whitespace() {
+            return Some((&val[..index], &val[index + ch.len_utf8()..]));
+        } else if ch == '"' && is_quote_start(prev) {
+            is_quoted = true;
+        }
+        prev = Some(ch);
+    }
+
+    Some((val, ""))
+}
+
+/// 去掉单词中的引号并处理转义。单词中没有引号时返回原内容
+pub(crate) fn unquote(word: &str) -> Cow<'_, str> {
+    if !word.contains('"') {
+        return Cow::Borrowed(word);
+    }
+
+    let mut result = String::new();
+    let mut is_quoted = false;
+    let mut is_escaped = false;
+    let mut prev = None;
+    for ch in word.chars() {
+        if is_quoted {
+            if is_escaped {
+                result.push(match ch {
+                    'n' => '\n',
+                    't' => '\t',
+                    _ => ch,
+                });
+                is_escaped = false;
+            } else if ch == '\\' {
+                is_escaped = true;
+            } else if ch == '"' {
+                is_quoted = false;
+            } else {
+                result.push(ch);
+            }
+        } else if ch == '"' && is_quote_start(prev) {
+            is_quoted = true;
+        } else {
+            result.push(ch);
+        }
+        prev = Some(ch);
+    }
+
+    Cow::Owned(result)
+}
+
+/// 获取描述等文本内容。整段内容使用引号括起时去掉引号
+pub(crate) fn get_text(line: &str) -> String {
+    let line = line.trim();
+    match get_word(line) {
+        Some((word, "")) if word.starts_with('"') => unquote(word).into_owned(),
+        _ => line.to_string(),
+    }
+}
+
+/// 去掉行中的注释并检查引号。不在引号中且位于单词开头的 # 之后为注释，\# 表示 # 本身
+pub(crate) fn strip_comment(segment_type: SegmentType, line: &str) -> Result<Cow<'_, str>, ParseError> {
+    let mut quote_start = None;
+    let mut is_escaped = false;
+    let mut prev = None;
+    let mut end = line.len();
+    // \# 中 \ 的位置
+    let mut escape_index_list = Vec::new();
+    let mut char_iter = line.char_indices().peekable();
+    while let Some((index, ch)) = char_iter.next() {
+        if quote_start.is_some() {
+            if is_escaped {
+                if !ESCAPE_CHAR_LIST.contains(&ch) {
+                    return Err(ParseError::new(
+                        segment_type,
+                        "one of the escape sequences \\\" \\\\ \\n \\t \\#",
+                        format!("'\\{}'", ch),
+                    ));
+                }
+                is_escaped = false;
+            } else if ch == '\\' {
+                is_escaped = true;
+            } else if ch == '"' {
+                quote_start = None;
+            }
+        } else if ch == '"' && is_quote_start(prev) {
+            quote_start = Some(index);
+        } else if ch == '\\' && matches!(char_iter.peek(), Some((_, '#'))) {
+            escape_index_list.push(index);
+            char_iter.next();
+            prev = Some('#');
+            continue;
+        } else if ch == '#' && prev.map(char::is_whitespace).unwrap_or(true) {
+            end = index;
+            break;
+        }
+        prev = Some(ch);
+    }
+    if let Some(index) = quote_start {
+        return Err(ParseError::new(
+            segment_type,
+            "closing '\"'",
+            format!("'{}'", line[index..].trim_end()),
+        ));
+    }
+
+    let line = &line[..end];
+    if escape_index_list.is_empty() {
+        return Ok(Cow::Borrowed(line));
+    }
+    let mut result = String::new();
+    let mut start = 0;
+    for index in escape_index_list {
+        result.push_str(&line[start..index]);
+        start = index + 1;
+    }
+    result.push_str(&line[start..]);
+    Ok(Cow::Owned(result))
+}
+
+/// 开始一个段的关键字。desc[{语言}] 中的语言不固定，单独判断
+const SEGMENT_KEYWORD_LIST: [&str; 15] = [
+    "syntax",
+    "module",
+    "fn",
+    "param",
+    "body",
+    "return",
+    "field",
+    "envelope",
+    "error",
+    "header",
+    "response_header",
+    "example",
+    "deprecated",
+    "since",
+    "tags",
+];
+
+/// 最初的文档注释语法中的关键字。之后加入的关键字在描述等自由文本中只有整行符合该关键字的格式时才开始一个段，
+/// 以免旧文档中以 example、error 等单词开头的描述被当做段
+const BASE_KEYWORD_LIST: [&str; 4] = ["module", "fn", "param", "return"];
+
+/// 行是否以段的关键字开头。行位于第一个段之前，之后加入的关键字需要符合格式
+pub(crate) fn is_segment_line(line: &str) -> bool {
+    match get_word(line.trim_start()) {
+        Some((word, rest)) => {
+            word.starts_with("desc[")
+                || BASE_KEYWORD_LIST.contains(&word)
+                || (SEGMENT_KEYWORD_LIST.contains(&word) && is_keyword_format(word, rest))
+        }
+        None => false,
+    }
+}
+
+/// 关键字之后的内容是否符合该关键字所在行的格式，如 example 之后为 request 或 response，
+/// since 之后为一个版本号。只用于之后加入的关键字
+fn is_keyword_format(keyword: &str, rest: &str) -> bool {
+    let rest = match strip_comment(SegmentType::None, rest) {
+        Ok(val) => val,
+        Err(_) => return false,
+    };
+    let mut word_list = Vec::new();
+    let mut left = rest.trim();
+    while let Some((word, val)) = get_word(left) {
+        word_list.push(word);
+        left = val.trim_start();
+    }
+
+    match keyword {
+        "header" | "response_header" | "field" => word_list.is_empty(),
+        // 错误码为数字或者不含小写字母的名字，如 USER_NOT_FOUND
+        "error" => match word_list.first() {
+            Some(val) => !val.chars().any(char::is_lowercase),
+            None => true,
+        },
+        "example" => matches!(word_list.first(), Some(&"request") | Some(&"response")),
+        "body" => word_list.first().is_some_and(|val| val.contains('/')),
+        "since" => word_list.len() == 1 && is_version(word_list[0]),
+        "syntax" => word_list.len() == 1 && word_list[0].chars().all(|val| val.is_ascii_digit()),
+        "envelope" => word_list.len() == 1,
+        "deprecated" => match word_list.len() {
+            0 | 1 => true,
+            2 => is_version(word_list[0]),
+            _ => false,
+        },
+        // 以逗号分隔的名字，名字中的空白字符需要使用引号
+        "tags" => {
+            !word_list.is_empty()
+                && rest.trim().split(',').all(|val| matches!(get_word(val.trim()), Some((_, ""))))
+        }
+        _ => true,
+    }
+}
+
+/// 单词是否与关键字相似，如大小写不同或者拼写错误
+fn is_like_keyword(word: &str, keyword: &str) -> bool {
+    let word = word.to_lowercase();
+    if word == keyword {
+        return true;
+    }
+    if word.chars().count() < 4 {
+        return false;
+    }
+
+    get_edit_distance(&word, keyword) <= 2
+}
+
+/// 获取两个字符串的编辑距离
+fn get_edit_distance(left: &str, right: &str) -> usize {
+    let right_list: Vec<char> = right.chars().collect();
+    let mut prev_row: Vec<usize> = (0..=right_list.len()).collect();
+    for (left_index, left_char) in left.chars().enumerate() {
+        let mut current_row = vec![left_index + 1];
+        for (right_index, right_char) in right_list.iter().enumerate() {
+            let cost = if left_char == *right_char { 0 } else { 1 };
+            let val = (prev_row[right_index] + cost)
+                .min(prev_row[right_index + 1] + 1)
+                .min(current_row[right_index] + 1);
+            current_row.push(val);
+        }
+        prev_row = current_row;
+    }
+
+    prev_row[right_list.len()]
+}
+
+/// 代码段类型
+#[derive(Debug, Eq, PartialOrd, PartialEq, Clone, Copy, Serialize_repr, Deserialize_repr)]
+#[repr(u8)]
+pub enum SegmentType {
+    None,
+    ModuleName,
+    FnName,
+    Param,
+    Return,
+    Error,
+    Header,
+    ResponseHeader,
+    Example,
+    Deprecated,
+    Since,
+    Tags,
+    Desc,
+    /// ```yaml 或 ```toml 代码块中的结构化文档
+    Block,
+    /// 声明文档注释语法的版本
+    Syntax,
+    /// 请求体
+    Body,
+    /// 响应的字段
+    Field,
+    /// 响应使用的信封
+    Envelope,
+}
+
+impl SegmentType {
+    /// 段之后的行是否是描述、返回值内容等自由文本
+    fn is_free_text(&self) -> bool {
+        matches!(
+            self,
+            SegmentType::None | SegmentType::FnName | SegmentType::Return | SegmentType::Example | SegmentType::Desc
+        )
+    }
+
+    /// 段中的每一行是否是独立的项。独立项出错后可以继续解析下一行，否则跳过整段
+    fn is_line_list(&self) -> bool {
+        matches!(
+            self,
+            SegmentType::Param
+                | SegmentType::Error
+                | SegmentType::Header
+                | SegmentType::ResponseHeader
+                | SegmentType::Body
+                | SegmentType::Field
+        )
+    }
+}
+
+impl fmt::Display for SegmentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SegmentType::None => "none",
+            SegmentType::ModuleName => "module",
+            SegmentType::FnName => "fn",
+            SegmentType::Param => "param",
+            SegmentType::Return => "return",
+            SegmentType::Error => "error",
+            SegmentType::Header => "header",
+            SegmentType::ResponseHeader => "response_header",
+            SegmentType::Example => "example",
+            SegmentType::Deprecated => "deprecated",
+            SegmentType::Since => "since",
+            SegmentType::Tags => "tags",
+            SegmentType::Desc => "desc",
+            SegmentType::Block => "block",
+            SegmentType::Syntax => "syntax",
+            SegmentType::Body => "body",
+            SegmentType::Field => "field",
+            SegmentType::Envelope => "envelope",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 解析语法版本行。格式为 syntax {版本号}，只能出现在其他段之前
+fn parse_syntax(is_first: bool, line: &str, is_before_segment: bool) -> Result<(), ParseError> {
+    if !is_first {
+        return Err(ParseError::new(
+            SegmentType::Syntax,
+            "a single syntax line",
+            format!("extra line '{}'", line.trim()),
+        ));
+    }
+    if !is_before_segment {
+        return Err(ParseError::new(
+            SegmentType::Syntax,
+            "syntax line before other segments",
+            format!("'syntax {}'", line.trim()),
+        ));
+    }
+
+    match get_word(line.trim()) {
+        Some((version, "")) if version.parse::<u32>() == Ok(GRAMMAR_VERSION) => Ok(()),
+        Some(_) => Err(ParseError::new(
+            SegmentType::Syntax,
+            format!("supported syntax version {}", GRAMMAR_VERSION),
+            format!("'{}'", line.trim()),
+        )),
+        None => Err(ParseError::new(SegmentType::Syntax, "syntax version", END_OF_LINE)),
+    }
+}
+
+/// 解析 desc[{语言}] 中的语言，语言统一为小写。不是 desc[ 开头时返回None
+fn get_desc_lang(word: &str) -> Option<Result<String, ParseError>> {
+    let lang = word.strip_prefix("desc[")?;
+    let lang = match lang.strip_suffix(']') {
+        Some(val) if !val.is_empty() && val.chars().all(|val| val.is_ascii_alphanumeric() || val == '-' || val == '_') => val,
+        _ => {
+            return Some(Err(ParseError::new(
+                SegmentType::Desc,
+                "language like desc[en] or desc[zh-CN]",
+                format!("'{}'", word),
+            )));
+        }
+    };
+
+    Some(Ok(lang.to_lowercase()))
+}
+
+/// 行已结束时的found描述
+pub(crate) const END_OF_LINE: &str = "end of line";
+
+/// 文档解析错误
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    /// 出错的注释行在注释列表中的索引
+    pub line_index: usize,
+    /// 出错时所在的段
+    pub segment_type: SegmentType,
+    /// 期望的内容
+    pub expected: String,
+    /// 实际找到的内容
+    pub found: String,
+}
+
+impl ParseError {
+    /// 创建错误。行索引由 parse_statement 填充
+    pub fn new(segment_type: SegmentType, expected: impl Into<String>, found: impl Into<String>) -> ParseError {
+        ParseError {
+            line_index: 0,
+            segment_type,
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} segment: expected {}, found {}",
+            self.segment_type, &self.expected, &self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 文档解析结果
+#[derive(Debug)]
+pub struct ParseResult {
+    /// 解析得到的文档。存在错误时内容不完整
+    pub document: ApiDocument,
+    /// 所有的错误
+    pub error_list: Vec<ParseError>,
+    /// 可疑但合法的内容
+    pub warning_list: Vec<ParseError>,
+    /// 每个参数所在的行，与 document.param_list 一一对应
+    pub param_line_list: Vec<usize>,
+}
+
+impl ParseResult {
+    /// 是否解析成功
+    pub fn is_ok(&self) -> bool {
+        self.error_list.is_empty()
+    }
+}
+
+/// 文档注释之外的API信息。来自 #[api(...)] 的属性参数以及函数签名
+#[derive(Debug, Clone, Default)]
+pub struct ApiExtraInfo {
+    /// 模块名。由 module = "..." 指定
+    pub module_name: Option<String>,
+    /// API名。由 name = "..." 指定
+    pub name: Option<String>,
+    /// 请求路径。由 path = "..." 指定
+    pub path: Option<String>,
+    /// 请求方法。由 method = "..." 指定
+    pub http_method: Option<String>,
+    /// 标签列表。由 tags = "a, b" 指定
+    pub tag_list: Option<Vec<String>>,
+    /// 是否废弃。由 deprecated 或者 deprecated = true 指定
+    pub deprecated: Option<bool>,
+    /// 所在的 #[api_module] 的模块名。文档注释与属性参数都未指定模块名时使用
+    pub parent_module: Option<String>,
+    /// 请求路径的前缀。来自所在的 #[api_module] ，或者由 base_path = "..." 指定
+    pub base_path: Option<String>,
+    /// 从函数签名推断出的参数
+    pub inferred_param_list: Vec<ApiParam>,
+    /// 可以在 param 段中使用 use {参数集合名} 引用的参数集合
+    pub param_set_list: Vec<ParamSetDocument>,
+}
+
+/// 函数文档转换。出错后会在段的边界恢复解析，以便一次报告所有错误
+pub fn parse_statement(doc_list: Vec<String>) -> ParseResult {
+    parse_statement_with_extra(doc_list, ApiExtraInfo::default())
+}
+
+/// 合并属性参数之前的注释解析结果
+pub(crate) struct LineParseResult {
+    pub document: ApiDocument,
+    pub error_list: Vec<ParseError>,
+    pub warning_list: Vec<ParseError>,
+    /// 每个段的第一行，用于合并属性参数时定位错误
+    pub segment_line_list: Vec<(SegmentType, usize)>,
+    /// 每个参数所在的行，用于路径参数检查时定位错误
+    pub param_line_list: Vec<usize>,
+}
+
+/// 函数文档转换，并合并文档注释之外的API信息。
+/// 属性参数与文档注释同时指定且不一致时报错；文档中手写的参数优先于推断出的参数。
+/// 注释中有 ```yaml 或 ```toml 代码块时使用代码块中的结构化文档，否则按行解析
+pub fn parse_statement_with_extra(doc_list: Vec<String>, extra_info: ApiExtraInfo) -> ParseResult {
+    let line_result = match parse_doc_block(&doc_list) {
+        Some(val) => val,
+        None => parse_line_list(&doc_list, &extra_info.param_set_list),
+    };
+    let LineParseResult {
+        document: mut result,
+        mut error_list,
+        warning_list,
+        segment_line_list,
+        mut param_line_list,
+    } = line_result;
+
+    let get_segment_line = |segment_type: SegmentType| {
+        segment_line_list
+            .iter()
+            .find(|val| val.0 == segment_type)
+            .map(|val| val.1)
+    };
+    // 属性参数没有对应的注释行，出错时定位到 fn 行，没有 fn 行时定位到第一行
+    let fn_line_index = get_segment_line(SegmentType::FnName).unwrap_or(0);
+
+    let is_fn_failed = error_list
+        .iter()
+        .any(|val| matches!(val.segment_type, SegmentType::FnName | SegmentType::Block));
+    let mut merge_error_list = Vec::new();
+    if let Some(val) = extra_info.module_name {
+        merge_attribute(&mut result.module_name, val, SegmentType::ModuleName, "module", &mut merge_error_list);
+    }
+    if let Some(val) = extra_info.name {
+        merge_attribute(&mut result.name, val, SegmentType::FnName, "name", &mut merge_error_list);
+    }
+    if let Some(val) = extra_info.path {
+        merge_attribute(&mut result.path, val, SegmentType::FnName, "path", &mut merge_error_list);
+    }
+    if let Some(val) = extra_info.http_method {
+        merge_attribute(&mut result.http_method, val, SegmentType::FnName, "method", &mut merge_error_list);
+    }
+    if let Some(val) = extra_info.tag_list {
+        if result.tag_list.is_empty() {
+            result.tag_list = val;
+        } else if result.tag_list != val {
+            merge_error_list.push(ParseError::new(
+                SegmentType::Tags,
+                format!("tags same as the api attribute '{}'", val.join(", ")),
+                format!("'{}'", result.tag_list.join(", ")),
+            ));
+        }
+    }
+    if let Some(val) = extra_info.deprecated {
+        if !val && result.deprecated {
+            merge_error_list.push(ParseError::new(
+                SegmentType::Deprecated,
+                "no deprecated segment as the api attribute is 'deprecated = false'",
+                "deprecated segment",
+            ));
+        }
+        result.deprecated = result.deprecated || val;
+    }
+    if result.module_name.is_empty() {
+        if let Some(val) = extra_info.parent_module {
+            result.module_name = val;
+        }
+    }
+    if let Some(val) = extra_info.base_path {
+        if !result.path.is_empty() {
+            result.path = join_base_path(&val, &result.path);
+        }
+    }
+    for mut err in merge_error_list {
+        err.line_index = get_segment_line(err.segment_type).unwrap_or(fn_line_index);
+        error_list.push(err);
+    }
+
+    // fn 行或者文档块出错时已经报告过，不再重复报告缺少的信息
+    if !is_fn_failed {
+        for (value, name) in [
+            (&result.name, "api name"),
+            (&result.path, "api path"),
+            (&result.http_method, "http method"),
+        ] {
+            if value.is_empty() {
+                let mut err = ParseError::new(
+                    SegmentType::FnName,
+                    format!("{} in fn line or api attribute", name),
+                    "nothing",
+                );
+                err.line_index = fn_line_index;
+                error_list.push(err);
+            }
+        }
+    }
+
+    // 已使用 body 段描述请求体时，不再推断请求体中的参数
+    let mut inferred_param_list = extra_info.inferred_param_list;
+    if result.request_body.is_some() {
+        inferred_param_list.retain(|val| !matches!(val.location, Some(ParamLocation::Body | ParamLocation::Form)));
+    }
+    // 推断出的参数没有对应的注释行，出错时定位到 fn 行
+    for param_item in merge_inferred_params(&mut result.param_list, inferred_param_list) {
+        result.param_list.push(param_item);
+        param_line_list.push(fn_line_index);
+    }
+    if !result.path.is_empty() {
+        check_path_param(&mut result, fn_line_index, &param_line_list, &mut error_list);
+    }
+
+    ParseResult {
+        document: result,
+        error_list,
+        warning_list,
+        param_line_list,
+    }
+}
+
+/// 按行解析函数文档。出错后会在段的边界恢复解析
+fn parse_line_list(doc_list: &[String], param_set_list: &[ParamSetDocument]) -> LineParseResult {
+    let mut result = ApiDocument::default();
+    let mut error_list = Vec::new();
+    let mut warning_list = Vec::new();
+    let mut segment_type = SegmentType::None;
+    // 当前段是否已出错。出错后跳过段内剩余的行，直到下一个段开始
+    let mut is_segment_failed = false;
+    // 每个段的第一行以及每个参数所在的行，用于合并属性参数以及路径参数检查时定位错误
+    let mut segment_line_list: Vec<(SegmentType, usize)> = Vec::new();
+    let mut param_line_list = Vec::new();
+    // 当前 desc[{语言}] 段的语言
+    let mut desc_lang = String::new();
+    // 最近一个参数或请求体字段所在路径上每一层的缩进
+    let mut field_indent_list: Vec<usize> = Vec::new();
+    // 当前段第一行的缩进
+    let mut segment_indent = 0;
+
+    // 提取函数的注释
+    for (line_index, line) in doc_list.iter().enumerate() {
+        // 查找到当前应该处理的段类型
+        let mut is_first = false;
+        // 参数等段中的 desc[{语言}] 行，做为上一项的多语言描述
+        let mut item_lang = None;
+        // 是否是 + 开头的行
+        let mut is_continuation = false;
+
+        let mut left_str;
+        let prefix_word;
+        match get_word(line.trim_start()){
+            Some(val)=>{
+                left_str=val.1;
+                prefix_word=val.0;
+            },
+            None=>{
+                prefix_word="";
+                left_str="";
+            }
+        }
+
+        // 参数等列表段中缩进比段的第一行深的行为段的内容，其中的关键字做为普通的名字，如名为 tags 的参数
+        let indent = line.chars().take_while(|val| val.is_whitespace()).count();
+        let keyword = if segment_type.is_line_list() && indent > segment_indent && !prefix_word.starts_with("desc[") {
+            ""
+        } else if segment_type.is_free_text()
+            && SEGMENT_KEYWORD_LIST.contains(&prefix_word)
+            && !BASE_KEYWORD_LIST.contains(&prefix_word)
+            && !is_keyword_format(prefix_word, left_str)
+        {
+            // 描述等自由文本中不符合关键字格式的行为普通文本，如 "example of usage"
+            ""
+        } else {
+            prefix_word
+        };
+
+        match keyword{
+            val if val.starts_with("desc[") => {
+                let lang = match get_desc_lang(val) {
+                    Some(Ok(val)) => val,
+                    Some(Err(mut err)) => {
+                        err.line_index = line_index;
+                        error_list.push(err);
+                        is_segment_failed = true;
+                        continue;
+                    }
+                    None => continue,
+                };
+                left_str = left_str.trim_start();
+                if matches!(
+                    segment_type,
+                    SegmentType::Param
+                        | SegmentType::Body
+                        | SegmentType::Field
+                        | SegmentType::Header
+                        | SegmentType::ResponseHeader
+                        | SegmentType::Return
+                ) {
+                    item_lang = Some(lang);
+                } else {
+                    is_first = true;
+                    segment_type = SegmentType::Desc;
+                    desc_lang = lang;
+                }
+            },
+            "module"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::ModuleName;
+            },
+            "fn"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::FnName;
+            },
+            "param"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Param;
+            },
+            "body"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Body;
+            },
+            "field"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Field;
+            },
+            "envelope"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Envelope;
+            },
+            "return"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Return;
+            },
+            "error"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Error;
+            },
+            "header"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Header;
+            },
+            "response_header"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::ResponseHeader;
+            },
+            "example"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Example;
+            },
+            "deprecated"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Deprecated;
+            },
+            "since"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Since;
+            },
+            "tags"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Tags;
+            },
+            "syntax"=>{
+                left_str = left_str.trim_start();
+                is_first = true;
+                segment_type = SegmentType::Syntax;
+            },
+            _=>{
+                if is_segment_failed {
+                    continue;
+                }
+                if line.trim_start().starts_with('+'){
+                    // 多行拼接使用+ 。之所以需要这个。是因为让使用者能保留多余的空字符以保证格式
+                    left_str = &line.trim_start()[1..];
+                    is_continuation = true;
+                }else{
+                    left_str = line.trim_start();
+                }
+            }
+        }
+
+        if is_first {
+            is_segment_failed = false;
+            segment_indent = indent;
+        }
+
+        // +开头的行以及示例、返回值、请求体示例的内容保留原样，其他行去掉注释
+        let is_raw_body = segment_type == SegmentType::Body
+            && result
+                .request_body
+                .as_ref()
+                .is_some_and(|val| val.kind == RequestBodyKind::Example);
+        let is_raw = is_continuation
+            || (!is_first
+                && item_lang.is_none()
+                && (is_raw_body || matches!(segment_type, SegmentType::Example | SegmentType::Return)));
+        let stripped_line = if is_raw {
+            Ok(Cow::Borrowed(left_str))
+        } else {
+            strip_comment(segment_type, left_str)
+        };
+        // 只有注释的行直接忽略
+        if let Ok(val) = &stripped_line {
+            if !is_first && val.trim().is_empty() && !left_str.trim().is_empty() {
+                continue;
+            }
+        }
+
+        // 按照对应段进行处理
+        let warning_count = warning_list.len();
+        let param_count = result.param_list.len();
+        let parse_result = match (segment_type, &item_lang, &stripped_line) {
+            (_, _, Err(err)) => Err(err.clone()),
+            (_, Some(lang), Ok(left_str)) => result.parse_item_lang_desc(segment_type, lang, left_str),
+            (segment_type, None, Ok(left_str)) => match segment_type {
+            SegmentType::ModuleName => result.parse_module_name(is_first, left_str),
+            SegmentType::FnName => result.parse_fn_line(is_first, left_str),
+            SegmentType::Param if is_first => {
+                field_indent_list.clear();
+                result.parse_param(is_first, left_str, &mut warning_list)
+            }
+            SegmentType::Param if is_continuation => result.append_param_desc(left_str),
+            SegmentType::Param if matches!(get_word(left_str.trim_start()), Some((USE_KEYWORD, _))) => {
+                // 参数集合中的参数不做为之后的行的父参数
+                field_indent_list.clear();
+                result.use_param_set(left_str, param_set_list)
+            }
+            SegmentType::Param => {
+                result.parse_param_item(indent, left_str, &mut field_indent_list, &mut warning_list)
+            }
+            SegmentType::Body if is_first => {
+                field_indent_list.clear();
+                result.parse_body(left_str)
+            }
+            SegmentType::Body if is_continuation => result.append_body_line(left_str),
+            SegmentType::Body => {
+                result.parse_body_item(indent, left_str, &mut field_indent_list, &mut warning_list)
+            }
+            SegmentType::Field if is_first => {
+                field_indent_list.clear();
+                result.parse_field(left_str, &mut warning_list)
+            }
+            SegmentType::Field if is_continuation => result.append_field_desc(left_str),
+            SegmentType::Field => {
+                result.parse_field_item(indent, left_str, &mut field_indent_list, &mut warning_list)
+            }
+            SegmentType::Return => result.parse_return(is_first, left_str),
+            SegmentType::Error => result.parse_error(is_first, left_str, &mut warning_list),
+            SegmentType::Header => result.parse_header(is_first, left_str, &mut warning_list),
+            SegmentType::ResponseHeader => {
+                result.parse_response_header(is_first, left_str, &mut warning_list)
+            }
+            SegmentType::Example => result.parse_example(is_first, left_str, &mut warning_list),
+            SegmentType::Deprecated => {
+                result.parse_deprecated(is_first, left_str, &mut warning_list)
+            }
+            SegmentType::Since => result.parse_since(is_first, left_str),
+            SegmentType::Envelope => result.parse_envelope(is_first, left_str),
+            SegmentType::Tags => result.parse_tags(is_first, left_str, &mut warning_list),
+            SegmentType::None => result.parse_desc(left_str),
+            SegmentType::Desc => result.parse_lang_desc(is_first, &desc_lang, left_str),
+            SegmentType::Syntax => parse_syntax(is_first, left_str, segment_line_list.is_empty()),
+            // 结构化文档不会按行解析
+            SegmentType::Block => Ok(()),
+            },
+        };
+        for warning_item in warning_list[warning_count..].iter_mut() {
+            warning_item.line_index = line_index;
+        }
+        if is_first {
+            segment_line_list.push((segment_type, line_index));
+        }
+        for _ in param_count..result.param_list.len() {
+            param_line_list.push(line_index);
+        }
+        if let Err(mut err) = parse_result {
+            err.line_index = line_index;
+            error_list.push(err);
+            // 请求体或响应字段第一行出错时，之后的行没有所属的请求体或响应
+            if !segment_type.is_line_list()
+                || (is_first && matches!(segment_type, SegmentType::Body | SegmentType::Field))
+            {
+                is_segment_failed = true;
+            }
+        }
+    }
+
+    LineParseResult {
+        document: result,
+        error_list,
+        warning_list,
+        segment_line_list,
+        param_line_list,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::document::{
+        parse_statement, parse_statement_with_extra, ApiDocument, ApiExtraInfo, ApiParam, ExampleKind,
+        ParamLocation, RequestBodyKind, SegmentType,
+    };
+
+    /// 把多行字符串转换为文档注释行，用于各个模块的测试
+    pub(crate) fn to_doc_list(doc: &str) -> Vec<String> {
+        doc.lines().map(|val| val.to_string()).collect()
+    }
+
+    #[test]
+    pub fn test_parse_statement() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post 获取用户
+             param
+                 Id int required 用户id
+                 Name string
+             return type Vec<UserInfo> 用户列表",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let doc_obj = &parse_result.document;
+        assert_eq!(doc_obj.module_name, "User");
+        assert_eq!(doc_obj.name, "GetUser");
+        assert_eq!(doc_obj.http_method, "post");
+        assert_eq!(doc_obj.desc, "获取用户");
+        assert_eq!(doc_obj.param_list.len(), 2);
+        assert!(doc_obj.param_list[0].required);
+        assert!(!doc_obj.param_list[1].required);
+        assert_eq!(doc_obj.response_list[0].type_name, "Vec<UserInfo>");
+        assert_eq!(doc_obj.response_list[0].desc, "用户列表");
+    }
+
+    #[test]
+    pub fn test_parse_error_segment() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             error
+                 1001 404 用户不存在
+                 1002 参数错误",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let error_list = &parse_result.document.error_list;
+        assert_eq!(error_list.len(), 2);
+        assert_eq!(error_list[0].code, "1001");
+        assert_eq!(error_list[0].http_status, Some(404));
+        assert_eq!(error_list[1].http_status, None);
+        assert_eq!(error_list[1].desc, "参数错误");
+
+        // 参数段以及响应字段中缩进更深的 error 为名字，不开始错误码段
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             param
+                 error bool 是否只查询出错的记录
+                 Id int
+             return 200
+             field
+                 error string 错误信息
+                 Code int 错误码",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let document = &parse_result.document;
+        assert!(document.error_list.is_empty());
+        assert_eq!(document.param_list.len(), 2);
+        assert_eq!(document.param_list[0].name, "error");
+        assert_eq!(document.response_list[0].field_list.len(), 2);
+        assert_eq!(document.response_list[0].field_list[0].name, "error");
+    }
+
+    #[test]
+    pub fn test_parse_header() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             header
+                 Token string required 登录令牌
+             response_header
+                 X-Total-Count int 总数",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let doc_obj = &parse_result.document;
+        assert_eq!(doc_obj.header_list.len(), 1);
+        assert!(doc_obj.header_list[0].required);
+        assert_eq!(doc_obj.response_header_list[0].name, "X-Total-Count");
+
+        // 参数段中缩进更深的 header、response_header 为参数名
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             param
+                 header string optional x
+                 response_header string optional y
+                 Id int",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        assert!(parse_result.warning_list.is_empty());
+        let doc_obj = &parse_result.document;
+        assert_eq!(doc_obj.param_list.len(), 3);
+        assert_eq!(doc_obj.param_list[0].name, "header");
+        assert_eq!(doc_obj.param_list[0].desc, "x");
+        assert_eq!(doc_obj.param_list[1].name, "response_header");
+        assert!(doc_obj.header_list.is_empty());
+        assert!(doc_obj.response_header_list.is_empty());
+    }
+
+    #[test]
+    pub fn test_parse_example() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             example request normal application/json
+                 +{\"Id\":1}
+             example response success 200
+                 +{
+                 +  \"Name\":\"test\"
+                 +}",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let example_list = &parse_result.document.example_list;
+        assert_eq!(example_list.len(), 2);
+        assert_eq!(example_list[0].kind, ExampleKind::Request);
+        assert_eq!(example_list[0].content_type, "application/json");
+        assert_eq!(example_list[1].http_status, Some(200));
+        assert_eq!(example_list[1].content, "{\r\n  \"Name\":\"test\"\r\n}");
+    }
+
+    #[test]
+    pub fn test_parse_deprecated_and_since() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             since 1.0
+             deprecated v1.2 GetUserV2",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let doc_obj = &parse_result.document;
+        assert_eq!(doc_obj.since, "1.0");
+        assert!(doc_obj.deprecated);
+        assert_eq!(doc_obj.deprecated_since, "v1.2");
+        assert_eq!(doc_obj.replacement, "GetUserV2");
+
+        // 参数段中缩进更深的 since、deprecated 为参数名
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUserList /v1/GetUserList post
+             param
+                 since string 起始时间
+                 deprecated bool 是否包含已废弃的用户
+                 Page int 页码",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let doc_obj = &parse_result.document;
+        assert_eq!(doc_obj.param_list.len(), 3);
+        assert_eq!(doc_obj.param_list[0].name, "since");
+        assert_eq!(doc_obj.param_list[1].name, "deprecated");
+        assert!(doc_obj.since.is_empty());
+        assert!(!doc_obj.deprecated);
+    }
+
+    #[test]
+    pub fn test_parse_tags() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             tags Payment, Admin,Payment",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        assert_eq!(parse_result.document.tag_list, vec!["Payment", "Admin"]);
+        assert_eq!(parse_result.warning_list.len(), 1);
+
+        // 参数段中缩进更深的 tags 为参数名，不开始新的段
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUserList /v1/GetUserList post
+             param
+                 tags string optional 标签过滤
+                 Page int 页码
+             tags Admin",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let document = &parse_result.document;
+        assert_eq!(document.param_list.len(), 2);
+        assert_eq!(document.param_list[0].name, "tags");
+        assert_eq!(document.param_list[0].desc, "标签过滤");
+        assert_eq!(document.tag_list, vec!["Admin"]);
+    }
+
+    #[test]
+    pub fn test_parse_error_line() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             param
+                 Id
+                 Name string requried 名字
+                 Age
+             return type",
+        ));
+        assert_eq!(parse_result.error_list.len(), 3);
+        let err = &parse_result.error_list[0];
+        assert_eq!(err.line_index, 3);
+        assert_eq!(err.segment_type, SegmentType::Param);
+        assert_eq!(err.found, "end of line");
+        assert_eq!(parse_result.error_list[1].line_index, 5);
+        assert_eq!(parse_result.error_list[2].segment_type, SegmentType::Return);
+
+        assert_eq!(parse_result.warning_list.len(), 1);
+        assert_eq!(parse_result.warning_list[0].line_index, 4);
+    }
+
+    #[test]
+    pub fn test_parse_path_param() {
+        let parse_result = parse_statement(to_doc_list(
+            " module Project
+             fn GetApiList /api/{project_id}/:version get
+             param
+                 ProjectId string required
+                 Version string in:path required
+                 Group string in:query optional 分组方式",
+        ));
+        assert!(parse_result.is_ok());
+        let param_list = &parse_result.document.param_list;
+        assert_eq!(param_list[0].location, Some(ParamLocation::Path));
+        assert_eq!(param_list[1].location, Some(ParamLocation::Path));
+        assert!(param_list[1].required);
+        assert_eq!(param_list[2].location, Some(ParamLocation::Query));
+        assert_eq!(param_list[2].desc, "分组方式");
+
+        let parse_result = parse_statement(to_doc_list(
+            " module Project
+             fn GetApiList /api/{project_id} get
+             param
+                 Id string in:path required
+                 Name string in:cookie",
+        ));
+        assert_eq!(parse_result.error_list.len(), 3);
+        assert_eq!(parse_result.error_list[0].line_index, 4);
+        assert_eq!(parse_result.error_list[1].line_index, 1);
+        assert_eq!(parse_result.error_list[2].line_index, 3);
+    }
+
+    #[test]
+    pub fn test_merge_inferred_params() {
+        let inferred_param_list = vec![
+            ApiParam {
+                name: "id".to_string(),
+                param_type: "u32".to_string(),
+                required: true,
+                location: Some(ParamLocation::Path),
+                ..ApiParam::default()
+            },
+            ApiParam {
+                param_type: "UserQuery".to_string(),
+                flatten: true,
+                location: Some(ParamLocation::Query),
+                ..ApiParam::default()
+            },
+            ApiParam {
+                param_type: "Json<UpdateUser>".to_string(),
+                flatten: true,
+                location: Some(ParamLocation::Body),
+                ..ApiParam::default()
+            },
+        ];
+        let parse_result = parse_statement_with_extra(
+            to_doc_list(
+                " module User
+                 fn UpdateUser /v1/user/{id} post
+                 param
+                     Id int required 用户id
+                     Name string in:body 名字",
+            ),
+            ApiExtraInfo {
+                inferred_param_list,
+                ..ApiExtraInfo::default()
+            },
+        );
+        assert!(parse_result.is_ok());
+        let param_list = &parse_result.document.param_list;
+        assert_eq!(param_list.len(), 3);
+        assert_eq!(param_list[0].param_type, "int");
+        assert_eq!(param_list[0].location, Some(ParamLocation::Path));
+        assert_eq!(param_list[2].param_type, "UserQuery");
+        assert_eq!(parse_result.param_line_list, vec![3, 4, 1]);
+    }
+
+    #[test]
+    pub fn test_merge_attribute() {
+        let extra_info = ApiExtraInfo {
+            module_name: Some("User".to_string()),
+            name: Some("GetUser".to_string()),
+            path: Some("/v1/user/{id}".to_string()),
+            http_method: Some("get".to_string()),
+            tag_list: Some(vec!["Admin".to_string()]),
+            ..ApiExtraInfo::default()
+        };
+        let parse_result = parse_statement_with_extra(
+            to_doc_list(
+                " 获取用户
+                 +详细说明
+                 param
+                     Id int required 用户id",
+            ),
+            extra_info.clone(),
+        );
+        assert!(parse_result.is_ok());
+        let doc_obj = &parse_result.document;
+        assert_eq!(doc_obj.module_name, "User");
+        assert_eq!(doc_obj.path, "/v1/user/{id}");
+        assert_eq!(doc_obj.tag_list, vec!["Admin"]);
+        assert_eq!(doc_obj.desc, "获取用户\r\n详细说明");
+        assert_eq!(doc_obj.param_list[0].location, Some(ParamLocation::Path));
+
+        let parse_result = parse_statement_with_extra(
+            to_doc_list(
+                " module Admin
+                 fn GetUser /v1/user/{id} post
+                 param
+                     Id int required 用户id",
+            ),
+            extra_info,
+        );
+        assert_eq!(parse_result.error_list.len(), 2);
+        assert_eq!(parse_result.error_list[0].line_index, 0);
+        assert_eq!(parse_result.error_list[1].line_index, 1);
+
+        let parse_result = parse_statement(to_doc_list(" 获取用户"));
+        assert_eq!(parse_result.error_list.len(), 3);
+
+        let parse_result = parse_statement_with_extra(
+            to_doc_list(" fn GetUser /user/{id} get"),
+            ApiExtraInfo {
+                parent_module: Some("User".to_string()),
+                base_path: Some("/v1/".to_string()),
+                inferred_param_list: vec![ApiParam {
+                    name: "id".to_string(),
+                    param_type: "u32".to_string(),
+                    ..ApiParam::default()
+                }],
+                ..ApiExtraInfo::default()
+            },
+        );
+        assert!(parse_result.is_ok());
+        assert_eq!(parse_result.document.module_name, "User");
+        assert_eq!(parse_result.document.path, "/v1/user/{id}");
+    }
+
+    #[test]
+    pub fn test_parse_param_constraints() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUserList /v1/GetUserList get
+             param
+                 Page int optional default=1 min=1 页码
+                 Status string enum=normal|locked default=deleted 状态
+                 Name string required len=2..20 pattern=^[a-z]+$ 名字
+                 Age int min=10 max=1
+                 Code string len=abc",
+        ));
+        let param_list = &parse_result.document.param_list;
+        assert_eq!(param_list[0].constraints.default, Some("1".to_string()));
+        assert_eq!(param_list[0].constraints.min, Some(1.0));
+        assert_eq!(param_list[0].desc, "页码");
+        assert_eq!(param_list[1].constraints.enum_list, vec!["normal", "locked"]);
+        assert!(param_list[2].required);
+        assert_eq!(param_list[2].constraints.min_len, Some(2));
+        assert_eq!(param_list[2].constraints.max_len, Some(20));
+        assert_eq!(param_list[2].constraints.pattern, Some("^[a-z]+$".to_string()));
+        assert!(param_list[2].constraints.min.is_none());
+
+        assert_eq!(parse_result.warning_list.len(), 1);
+        assert_eq!(parse_result.warning_list[0].line_index, 4);
+        assert_eq!(parse_result.error_list.len(), 2);
+        assert_eq!(parse_result.error_list[0].line_index, 6);
+        assert_eq!(parse_result.error_list[1].line_index, 7);
+    }
+
+    #[test]
+    pub fn test_parse_comment_and_quote() {
+        let parse_result = parse_statement(to_doc_list(
+            " syntax 1
+             # 只有注释的行
+             module User # 用户模块
+             fn GetUser　/v1/GetUser　post　获取用户 \\# 不是注释 # 注释
+             param
+                 \"user name\" string required \"名字 # 不是注释\"
+                 \"fn\" string default=\"a b\" enum=\"a b\"|c
+                 Remark string \"备注\\n第二行\"
+             error
+                 1001 用户不存在 # 注释
+             example request normal
+                 # 示例内容中的注释保留
+             return
+                 # 返回内容中的注释保留",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let document = &parse_result.document;
+        assert_eq!(document.module_name, "User");
+        assert_eq!(document.name, "GetUser");
+        assert_eq!(document.path, "/v1/GetUser");
+        assert_eq!(document.http_method, "post");
+        assert_eq!(document.desc, "获取用户 # 不是注释");
+        assert_eq!(document.param_list[0].name, "user name");
+        assert_eq!(document.param_list[0].desc, "名字 # 不是注释");
+        assert_eq!(document.param_list[1].name, "fn");
+        assert_eq!(document.param_list[1].constraints.default, Some("a b".to_string()));
+        assert_eq!(document.param_list[1].constraints.enum_list, vec!["a b", "c"]);
+        assert_eq!(document.param_list[2].desc, "备注\n第二行");
+        assert_eq!(document.error_list[0].desc, "用户不存在");
+        assert_eq!(document.example_list[0].content, "# 示例内容中的注释保留");
+        assert_eq!(document.response_list[0].content, "# 返回内容中的注释保留");
+
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             syntax 1
+             fn GetUser /v1/GetUser post \"获取用户
+             param
+                 Name string \"名字\\x\"",
+        ));
+        assert_eq!(parse_result.error_list.len(), 3);
+        assert_eq!(parse_result.error_list[0].segment_type, SegmentType::Syntax);
+        assert_eq!(parse_result.error_list[0].line_index, 1);
+        assert_eq!(parse_result.error_list[1].line_index, 2);
+        assert_eq!(parse_result.error_list[2].line_index, 4);
+
+        let parse_result = parse_statement(to_doc_list(" syntax 2"));
+        assert_eq!(parse_result.error_list[0].segment_type, SegmentType::Syntax);
+    }
+
+    #[test]
+    pub fn test_parse_nested_param() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn AddUser /v1/AddUser post
+             param
+                 Id int required 用户id
+                     用户的唯一标识
+                 +  第三行
+                 User object required 用户
+                     Name string 名字
+                     desc[en] Name
+                     AddressList array 地址列表
+                         City string 城市
+                     Age int 年龄
+                 Page int 页码
+                     Size int 每页数量",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let param_list = &parse_result.document.param_list;
+        assert_eq!(param_list.len(), 3);
+        assert_eq!(parse_result.param_line_list, vec![3, 6, 12]);
+        assert_eq!(param_list[0].desc, "用户id\r\n用户的唯一标识\r\n  第三行");
+        let user = &param_list[1];
+        assert_eq!(user.children.len(), 3);
+        assert_eq!(user.children[0].desc_map["en"], "Name");
+        assert_eq!(user.children[1].children[0].name, "City");
+        assert_eq!(user.children[2].name, "Age");
+        assert_eq!(param_list[2].name, "Page");
+        assert!(param_list[2].children.is_empty());
+
+        // Page 不是容器类型，缩进更深的参数行做为描述并给出警告
+        assert_eq!(param_list[2].desc, "页码\r\nSize int 每页数量");
+        assert_eq!(parse_result.warning_list.len(), 1);
+        assert_eq!(parse_result.warning_list[0].line_index, 13);
+    }
+
+    #[test]
+    pub fn test_parse_body() {
+        let parse_result = parse_statement_with_extra(
+            to_doc_list(
+                " module User
+                 fn UploadAvatar /v1/UploadAvatar post
+                 body multipart/form-data 头像 # 注释
+                     UserId int required 用户id
+                     Avatar file required 头像文件
+                     desc[en] Avatar file
+                     Meta object 附加信息
+                         Size int 文件大小
+                 param
+                     Page int 页码",
+            ),
+            ApiExtraInfo {
+                inferred_param_list: vec![ApiParam {
+                    name: "form".to_string(),
+                    param_type: "UploadForm".to_string(),
+                    flatten: true,
+                    location: Some(ParamLocation::Form),
+                    ..ApiParam::default()
+                }],
+                ..ApiExtraInfo::default()
+            },
+        );
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        assert!(parse_result.warning_list.is_empty());
+        let document = &parse_result.document;
+        // 已有请求体时不再推断表单参数
+        assert_eq!(document.param_list.len(), 1);
+        let body = document.request_body.as_ref().unwrap();
+        assert_eq!(body.content_type, "multipart/form-data");
+        assert_eq!(body.kind, RequestBodyKind::Fields);
+        assert_eq!(body.desc, "头像");
+        assert_eq!(body.field_list.len(), 3);
+        assert_eq!(body.field_list[1].desc_map["en"], "Avatar file");
+        assert_eq!(body.field_list[2].children[0].name, "Size");
+
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn AddUser /v1/AddUser post
+             body application/json type UserInfo 用户信息
+             body application/json example
+                 {\"Name\": \"a # b\"}",
+        ));
+        assert_eq!(parse_result.error_list.len(), 1);
+        assert_eq!(parse_result.error_list[0].segment_type, SegmentType::Body);
+        assert_eq!(parse_result.error_list[0].line_index, 3);
+        let body = parse_result.document.request_body.as_ref().unwrap();
+        assert_eq!(body.kind, RequestBodyKind::Type);
+        assert_eq!(body.type_name, "UserInfo");
+
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn AddUser /v1/AddUser post
+             body application/json example
+                 {\"Name\": \"a # b\"}
+             body application/json",
+        ));
+        assert_eq!(parse_result.error_list[0].line_index, 4);
+        let body = parse_result.document.request_body.as_ref().unwrap();
+        assert_eq!(body.kind, RequestBodyKind::Example);
+        assert_eq!(body.content, "{\"Name\": \"a # b\"}");
+
+        // file 类型的字段只能出现在 multipart/form-data 请求体中
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn AddUser /v1/AddUser post
+             body application/json
+                 Avatar file 头像
+             return type UserInfo",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        assert_eq!(parse_result.warning_list.len(), 1);
+        assert_eq!(parse_result.warning_list[0].line_index, 3);
+
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn AddUser /v1/AddUser post
+             body application/json type UserInfo
+                 Name string",
+        ));
+        assert_eq!(parse_result.error_list.len(), 1);
+        assert_eq!(parse_result.error_list[0].line_index, 3);
+
+        // 参数段中缩进更深的 body 为参数名，段首的 body 必须指定内容类型
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn AddUser /v1/AddUser post
+             param
+                 body string optional 内容
+             body string optional y
+                 Name string",
+        ));
+        assert_eq!(parse_result.document.param_list[0].name, "body");
+        assert!(parse_result.document.request_body.is_none());
+        assert_eq!(parse_result.error_list.len(), 1);
+        assert_eq!(parse_result.error_list[0].segment_type, SegmentType::Body);
+        assert_eq!(parse_result.error_list[0].line_index, 4);
+    }
+
+    #[test]
+    pub fn test_parse_response() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             return 200 application/json type UserInfo 用户信息
+             desc[en] User info
+             return 400 参数错误
+                 +{\"Code\": 1001}
+             return 404 application/json type ErrorBody
+             return 404 text/plain 用户不存在
+             return 404 application/json",
+        ));
+        assert_eq!(parse_result.error_list.len(), 1);
+        assert_eq!(parse_result.error_list[0].segment_type, SegmentType::Return);
+        assert_eq!(parse_result.error_list[0].line_index, 8);
+        let response_list = &parse_result.document.response_list;
+        assert_eq!(response_list.len(), 4);
+        assert_eq!(response_list[0].http_status, Some(200));
+        assert_eq!(response_list[0].content_type, "application/json");
+        assert_eq!(response_list[0].type_name, "UserInfo");
+        assert_eq!(response_list[0].desc_map["en"], "User info");
+        assert_eq!(response_list[1].desc, "参数错误");
+        assert_eq!(response_list[1].content, "{\"Code\": 1001}");
+        assert!(!response_list[1].is_success());
+        assert_eq!(response_list[2].type_name, "ErrorBody");
+        assert_eq!(response_list[3].content_type, "text/plain");
+
+        // 旧版本的doc.data中只有一个返回值，return type {类型名} {描述} 的类型名保存在 ReturnDesc 中
+        let mut document: ApiDocument = serde_json::from_str(
+            r#"{"ModuleName":"User","HttpMethod":"post","Name":"GetUser","Path":"/v1/GetUser","Desc":"获取用户",
+                "ParamList":[{"Name":"Id","ParamType":"int","Required":true,"Desc":"用户id"}],
+                "ReturnContentType":1,"ReturnContent":"\r\n{\"Name\":\"test\"}","ReturnDesc":"UserInfo 用户信息"}"#,
+        )
+        .unwrap();
+        document.migrate_legacy_return();
+        assert_eq!(document.param_list[0].name, "Id");
+        assert_eq!(document.response_list.len(), 1);
+        assert_eq!(document.response_list[0].type_name, "UserInfo");
+        assert_eq!(document.response_list[0].desc, "用户信息");
+        assert_eq!(document.response_list[0].content, "{\"Name\":\"test\"}");
+        assert!(document.response_list[0].is_success());
+        let content = serde_json::to_string(&document).unwrap();
+        assert!(!content.contains("ReturnContentType"));
+
+        let mut document: ApiDocument = serde_json::from_str(
+            r#"{"ModuleName":"User","HttpMethod":"post","Name":"GetUser","Path":"/v1/GetUser","Desc":"",
+                "ParamList":[],"ReturnContentType":1,"ReturnContent":"","ReturnDesc":"Vec<UserInfo>"}"#,
+        )
+        .unwrap();
+        document.migrate_legacy_return();
+        assert_eq!(document.response_list[0].type_name, "Vec<UserInfo>");
+        assert!(document.response_list[0].desc.is_empty());
+
+        // return string {描述} 以及只有描述的返回值
+        let mut document: ApiDocument = serde_json::from_str(
+            r#"{"ModuleName":"User","HttpMethod":"post","Name":"GetUser","Path":"/v1/GetUser","Desc":"",
+                "ParamList":[],"ReturnContentType":0,"ReturnContent":"\r\n{}","ReturnDesc":"用户 信息"}"#,
+        )
+        .unwrap();
+        document.migrate_legacy_return();
+        assert_eq!(document.response_list[0].content, "{}");
+        assert_eq!(document.response_list[0].desc, "用户 信息");
+        assert!(document.response_list[0].type_name.is_empty());
+    }
+
+    #[test]
+    pub fn test_parse_response_field() {
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             field
+                 Code int 错误码
+             fn GetUser /v1/GetUser post
+             return 200 application/json 成功
+             field
+                 Code int 错误码
+                 Data object nullable 数据
+                     Name string 名字
+                     desc[en] Name
+                     Age int nulable 年龄
+                 +  第二行
+             return 404 application/json
+             field
+                 Code int",
+        ));
+        assert_eq!(parse_result.error_list.len(), 1);
+        assert_eq!(parse_result.error_list[0].segment_type, SegmentType::Field);
+        assert_eq!(parse_result.error_list[0].line_index, 1);
+        assert_eq!(parse_result.warning_list.len(), 1);
+        assert_eq!(parse_result.warning_list[0].line_index, 10);
+
+        let response_list = &parse_result.document.response_list;
+        let field_list = &response_list[0].field_list;
+        assert_eq!(field_list.len(), 2);
+        assert!(field_list[0].required);
+        assert!(!field_list[1].required);
+        assert_eq!(field_list[1].children[0].desc_map["en"], "Name");
+        assert_eq!(field_list[1].children[1].desc, "nulable 年龄\r\n  第二行");
+        assert_eq!(response_list[1].field_list[0].name, "Code");
+
+        // 缩进更深的 field 为字段名，不开始新的字段段
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post
+             param
+                 field string 排序字段
+             return 200
+             field
+                 Error object 错误
+                     field string 出错的字段
+                 Code int 错误码",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let document = &parse_result.document;
+        assert_eq!(document.param_list[0].name, "field");
+        let field_list = &document.response_list[0].field_list;
+        assert_eq!(field_list.len(), 2);
+        assert_eq!(field_list[0].children[0].name, "field");
+        assert_eq!(field_list[1].name, "Code");
+    }
+
+    #[test]
+    pub fn test_parse_lang_desc() {
+        let parse_result = parse_statement(to_doc_list(
+            " 获取用户列表
+             desc[en] Get user list
+             +  of the current project
+             desc[ZH] 获取用户列表
+             module User
+             fn GetUserList /v1/GetUserList get
+             param
+                 Page int 页码
+                 desc[en] Page index
+             return int 总数
+                 desc[en] Total count
+             desc[en] repeated
+             desc[] empty",
+        ));
+        let document = &parse_result.document;
+        assert_eq!(document.desc, "获取用户列表");
+        assert_eq!(document.desc_map["en"], "Get user list\r\n  of the current project");
+        assert_eq!(document.desc_map["zh"], "获取用户列表");
+        assert_eq!(document.param_list[0].desc, "页码");
+        assert_eq!(document.param_list[0].desc_map["en"], "Page index");
+        assert_eq!(document.response_list[0].desc_map["en"], "Total count");
+        assert_eq!(document.get_lang_list(), vec!["en", "zh"]);
+
+        let mut localized = document.clone();
+        localized.localize("en");
+        assert_eq!(localized.desc, "Get user list\r\n  of the current project");
+        assert_eq!(localized.param_list[0].desc, "Page index");
+        assert_eq!(localized.response_list[0].desc, "Total count");
+        localized.localize("fr");
+        assert_eq!(localized.param_list[0].desc, "Page index");
+
+        assert_eq!(parse_result.error_list.len(), 2);
+        assert_eq!(parse_result.error_list[0].line_index, 11);
+        assert_eq!(parse_result.error_list[1].line_index, 12);
+    }
+
+    #[test]
+    pub fn test_keyword_in_free_text() {
+        // 最初语法的文档中以之后加入的关键字开头的描述、返回值内容不开始新的段
+        let parse_result = parse_statement(to_doc_list(
+            " example of usage: call it after login
+             error handling is done by the caller
+             module User
+             fn GetUser /v1/GetUser post 获取用户
+             since the first release
+             tags are not checked
+             return json
+             header is not included
+             field values are
+             +{\"Id\": 1}",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        assert!(parse_result.warning_list.is_empty(), "{:?}", parse_result.warning_list);
+        let document = &parse_result.document;
+        assert_eq!(
+            document.desc,
+            "example of usage: call it after login\r\nerror handling is done by the caller\r\n获取用户\r\n\
+             since the first release\r\ntags are not checked"
+        );
+        assert_eq!(document.response_list[0].content, "header is not included\r\nfield values are\r\n{\"Id\": 1}");
+        assert!(document.error_list.is_empty());
+        assert!(document.tag_list.is_empty());
+        assert!(document.since.is_empty());
+
+        // 符合关键字格式的行仍然开始新的段
+        let parse_result = parse_statement(to_doc_list(
+            " module User
+             fn GetUser /v1/GetUser post 获取用户
+             since 1.2.0
+             tags Admin, \"User Info\"
+             return 200 UserInfo
+             error
+                 USER_NOT_FOUND 404 用户不存在
+             example response success
+             +{}",
+        ));
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+        let document = &parse_result.document;
+        assert_eq!(document.since, "1.2.0");
+        assert_eq!(document.tag_list, vec!["Admin", "User Info"]);
+        assert_eq!(document.error_list[0].code, "USER_NOT_FOUND");
+        assert_eq!(document.example_list.len(), 1);
+    }
 }
@@ -12,19 +12,30 @@ pub const FILE_NAME: &str = "doc.data";
 pub enum ItemType {
     Header,
     Api,
+    Type,
+    Module,
+    /// 信封。Api 中保存的是未使用信封的响应，需要使用 ApiDocument::apply_envelope 组合
+    Envelope,
 }
 
 static FILE_OBJ: Lazy<Mutex<Option<File>>> = Lazy::new(|| Mutex::new(None));
 
+/// 指定文档文件的路径，文件已存在时清空。之后保存的项都写入此文件，未指定时为当前目录下的 doc.data
+pub fn set_file_path(file_path: &str) -> Result<(), String> {
+    let file_obj = File::create(file_path).map_err(|err| err.to_string())?;
+    *FILE_OBJ.lock().unwrap() = Some(file_obj);
+    Ok(())
+}
+
 /// 保存项
 pub fn save_item<T: Serialize>(item_type: ItemType, data_item: &T) -> Result<(), String> {
     let ser_result = serde_json::to_string(data_item);
     match ser_result {
         Ok(val) => {
-            return save_item_str(item_type, val.as_str());
+            save_item_str(item_type, val.as_str())
         }
         Err(err) => {
-            return Err(err.to_string());
+            Err(err.to_string())
         }
     }
 }
@@ -55,7 +66,7 @@ pub fn save_item_str(item_type: ItemType, data_item: &str) -> Result<(), String>
 
     // 写入文件
     let file_obj = file_obj_opt.as_mut().unwrap();
-    let result = file_obj.write_all(&*result_bytes);
+    let result = file_obj.write_all(&result_bytes);
     match result {
         Ok(_) => Ok(()),
         Err(err) => Err(err.to_string()),
@@ -78,15 +89,12 @@ pub struct Item {
 /// 获取数据列表
 pub fn get_val_list(file_path_str: &str) -> Result<Vec<Item>, String> {
     let file_path = Path::new(file_path_str);
-    let file_data;
-    match std::fs::read(file_path) {
-        Ok(bytes_data) => {
-            file_data = bytes_data;
-        }
+    let file_data = match std::fs::read(file_path) {
+        Ok(bytes_data) => bytes_data,
         Err(err) => {
             return Err(err.to_string());
         }
-    }
+    };
 
     let mut result = Vec::new();
 
@@ -95,23 +103,20 @@ pub fn get_val_list(file_path_str: &str) -> Result<Vec<Item>, String> {
         if left_data.len() < 4 {
             break;
         }
-        let len: u32;
+
         let len_bytes: [u8; 4] = [left_data[0], left_data[1], left_data[2], left_data[3]];
-        len = u32::from_le_bytes(len_bytes);
+        let len: u32 = u32::from_le_bytes(len_bytes);
         if left_data.len() < (len + 4) as usize {
             break;
         }
 
         let item_type = left_data[4] as u32;
-        let content;
-        match std::str::from_utf8(&left_data[5usize..(4 + len) as usize]) {
-            Ok(val) => {
-                content = val.to_string();
-            }
+        let content = match std::str::from_utf8(&left_data[5usize..(4 + len) as usize]) {
+            Ok(val) => val.to_string(),
             Err(err) => {
                 return Err(err.to_string());
             }
-        }
+        };
 
         result.push(Item { item_type, content });
 
@@ -122,18 +127,36 @@ pub fn get_val_list(file_path_str: &str) -> Result<Vec<Item>, String> {
         left_data = &left_data[(4 + len) as usize..];
     }
 
-    return Ok(result);
+    Ok(result)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::file::get_val_list;
+    use crate::doc_type::TypeDocument;
+    use crate::file::{get_doc_file_path, get_val_list, save_item, save_item_str, set_file_path, ItemType};
 
     #[test]
     pub fn test_load_doc() {
-        let item_list = get_val_list("./").unwrap();
-        for item in item_list.iter() {
-            println!("type:{} content:{}", item.item_type, &item.content);
-        }
+        let dir = std::env::temp_dir().join(format!("doc_def_file_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file_path = get_doc_file_path(dir.to_str().unwrap());
+        set_file_path(&file_path).unwrap();
+
+        save_item_str(ItemType::Header, "文档头").unwrap();
+        let type_doc = TypeDocument {
+            name: "UserInfo".to_string(),
+            ..TypeDocument::default()
+        };
+        save_item(ItemType::Type, &type_doc).unwrap();
+
+        let item_list = get_val_list(&file_path).unwrap();
+        assert_eq!(item_list.len(), 2);
+        assert_eq!(item_list[0].item_type, ItemType::Header as u32);
+        assert_eq!(item_list[0].content, "文档头");
+        assert_eq!(item_list[1].item_type, ItemType::Type as u32);
+        let load_doc: TypeDocument = serde_json::from_str(&item_list[1].content).unwrap();
+        assert_eq!(load_doc.name, "UserInfo");
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -1,2 +1,7 @@
+pub mod doc_block;
+pub mod doc_envelope;
+pub mod doc_module;
+pub mod doc_params;
+pub mod doc_type;
 pub mod document;
 pub mod file;
@@ -23,110 +23,292 @@
 /// ````
 use once_cell::sync::Lazy;
 use proc_macro::TokenStream;
-use quote::{quote, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
 use std::collections::HashMap;
 use std::sync::Mutex;
 use syn::spanned::Spanned;
 use syn::Lit;
-use syn::{parse_macro_input, Meta};
+use syn::{parse_macro_input, Meta, NestedMeta};
+
+mod param_set;
 
 static ALL_API: Lazy<Mutex<HashMap<String, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// 以带模块路径的类型名为key，不同模块中可以有同名的类型
+static ALL_TYPE: Lazy<Mutex<HashMap<String, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static ALL_MODULE: Lazy<Mutex<HashMap<String, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static ALL_ENVELOPE: Lazy<Mutex<HashMap<String, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 static DOC_HEADER_IS_SET: Lazy<Mutex<bool>> = Lazy::new(|| Mutex::new(false));
+/// 默认信封名。只能有一个默认信封
+static DEFAULT_ENVELOPE: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
 
-// 用于标记API文档
+// 用于标记API文档。可以标记在函数、impl中的方法、trait中的方法以及整个impl块上
 #[proc_macro_attribute]
-pub fn api(_arg: TokenStream, input: TokenStream) -> TokenStream {
-    let fn_item = parse_macro_input!(input as syn::ItemFn);
+pub fn api(arg: TokenStream, input: TokenStream) -> TokenStream {
+    let arg_list = parse_macro_input!(arg as syn::AttributeArgs);
+    let api_arg = match parse_api_arg(&arg_list) {
+        Ok(val) => val,
+        Err(err) => return proc_macro::TokenStream::from(err.to_compile_error()),
+    };
 
-    let mut doc_list = Vec::new();
-    for attr_item in fn_item.attrs.iter() {
-        if let Ok(meta) = attr_item.parse_meta() {
-            match meta {
-                Meta::Path(_val) => {
-                    continue;
-                }
-                Meta::List(_meta_list) => {
-                    continue;
-                }
-                Meta::NameValue(val) => {
-                    let token_name = val.path.to_token_stream().to_string();
-                    if token_name.as_str() == "doc" {
-                        if let Lit::Str(val) = &val.lit {
-                            doc_list.push(val.value());
-                        }
-                    }
-                }
+    if let Ok(impl_item) = syn::parse::<syn::ItemImpl>(input.clone()) {
+        return expand_api_impl(api_arg, impl_item, save_api).into();
+    }
+
+    // 函数与impl中的方法语法相同，都按函数处理
+    if let Ok(mut fn_item) = syn::parse::<syn::ItemFn>(input.clone()) {
+        let span = fn_item.span();
+        let mut expand_result = expand_api(api_arg, !arg_list.is_empty(), &mut fn_item.attrs, &fn_item.sig, span);
+        save_api(&mut expand_result, span);
+        insert_into_block(&mut fn_item.block, expand_result);
+        return quote!(#fn_item).into();
+    }
+
+    let method_item = parse_macro_input!(input as syn::TraitItemMethod);
+    expand_api_trait_method(api_arg, !arg_list.is_empty(), method_item, save_api).into()
+}
+
+/// 保存API文档的方法。宏展开时为 save_api ，测试时可以不保存
+type SaveApiFn = fn(&mut ApiExpandResult, proc_macro2::Span);
+
+/// 处理标记在trait方法上的 #[api]。
+/// 没有默认实现的方法无法放置警告，trait中也不能使用 const _ ，所以警告做为错误报告
+fn expand_api_trait_method(
+    api_arg: ApiArg,
+    has_attr_arg: bool,
+    mut method_item: syn::TraitItemMethod,
+    save: SaveApiFn,
+) -> proc_macro2::TokenStream {
+    let span = method_item.span();
+    let mut expand_result = expand_api(api_arg, has_attr_arg, &mut method_item.attrs, &method_item.sig, span);
+    save(&mut expand_result, span);
+    match method_item.default.as_mut() {
+        Some(block) => {
+            insert_into_block(block, expand_result);
+            quote!(#method_item)
+        }
+        None => {
+            let mut error_list = expand_result.error_list;
+            for (span, message) in expand_result.warning_list {
+                let message = format!("{} (trait method without default body can not report warnings)", message);
+                error_list.push(syn::Error::new(span, message).to_compile_error());
             }
+            quote!(#(#error_list)* #method_item)
         }
     }
+}
 
-    if doc_list.len() == 0 {
-        let err = syn::Error::new(fn_item.span(), "excepted api document");
-        return proc_macro::TokenStream::from(err.to_compile_error());
+/// 处理标记在impl块上的 #[api]。
+/// 文档注释中有 fn 行的方法都做为API，模块名以及请求路径前缀会应用到所有的方法上。
+/// 方法上已有 #[api] 时由其自己处理，每个方法的错误单独报告
+fn expand_api_impl(api_arg: ApiArg, mut impl_item: syn::ItemImpl, save: SaveApiFn) -> proc_macro2::TokenStream {
+    let extra_info = &api_arg.extra_info;
+    if extra_info.name.is_some()
+        || extra_info.path.is_some()
+        || extra_info.http_method.is_some()
+        || extra_info.tag_list.is_some()
+        || extra_info.deprecated.is_some()
+    {
+        let err = syn::Error::new(
+            impl_item.span(),
+            "only module, base_path and check_params can be used on impl block",
+        );
+        return err.to_compile_error();
     }
 
-    let api_doc = doc_def::document::parse_statement(doc_list);
-    match api_doc {
-        Ok(doc_obj) => {
-            let api_key = format!("{}_{}", &doc_obj.module_name, &doc_obj.name);
-            let mut all_api_map = ALL_API.lock().unwrap();
-            if all_api_map.contains_key(&api_key) {
-                let err = syn::Error::new(
-                    fn_item.span(),
-                    &format!(
-                        "repeated api define. module:{} fn:{}",
-                        &doc_obj.module_name, &doc_obj.name
-                    ),
-                );
-                return proc_macro::TokenStream::from(err.to_compile_error());
-            }
-            all_api_map.insert(api_key, true);
+    let module_name = extra_info
+        .module_name
+        .clone()
+        .or_else(|| extra_info.parent_module.clone());
+    let base_path = extra_info.base_path.clone();
+    for item in impl_item.items.iter_mut() {
+        let method_item = match item {
+            syn::ImplItem::Method(val) => val,
+            _ => continue,
+        };
 
-            let result = doc_def::file::save_item(doc_def::file::ItemType::Api, &doc_obj);
-            match result {
-                Ok(_) => {}
-                Err(err) => {
-                    let err = syn::Error::new(fn_item.span(), err.as_str());
-                    return proc_macro::TokenStream::from(err.to_compile_error());
-                }
+        if let Some(attr) = method_item.attrs.iter_mut().find(|val| is_macro_attr(val, "api")) {
+            let mut arg_list = Vec::new();
+            if let Some(val) = &module_name {
+                arg_list.push(quote!(parent_module = #val));
+            }
+            if let Some(val) = &base_path {
+                arg_list.push(quote!(parent_base_path = #val));
             }
+            if !arg_list.is_empty() {
+                append_attr_arg(attr, quote!(#(#arg_list),*));
+            }
+            continue;
         }
-        Err(err) => {
-            let err = syn::Error::new(fn_item.span(), err.as_str());
-            return proc_macro::TokenStream::from(err.to_compile_error());
+
+        let is_api = get_doc_list(&method_item.attrs)
+            .iter()
+            .any(|val| val.split_whitespace().next() == Some("fn"));
+        if !is_api {
+            continue;
         }
+
+        let method_arg = ApiArg {
+            extra_info: doc_def::document::ApiExtraInfo {
+                parent_module: module_name.clone(),
+                base_path: base_path.clone(),
+                ..doc_def::document::ApiExtraInfo::default()
+            },
+            is_check_params: api_arg.is_check_params,
+        };
+        let span = method_item.span();
+        let mut expand_result = expand_api(method_arg, true, &mut method_item.attrs, &method_item.sig, span);
+        save(&mut expand_result, span);
+        insert_into_block(&mut method_item.block, expand_result);
     }
 
-    quote!(#fn_item).into()
+    quote!(#impl_item)
 }
 
-// 用于标记API文档的头部信息
-#[proc_macro_attribute]
-pub fn doc_header(_arg: TokenStream, input: TokenStream) -> TokenStream {
-    let fn_item = parse_macro_input!(input as syn::ItemFn);
+/// API文档的解析结果，错误以及警告需要放到函数体中
+struct ApiExpandResult {
+    error_list: Vec<proc_macro2::TokenStream>,
+    /// 警告的位置以及内容
+    warning_list: Vec<(proc_macro2::Span, String)>,
+    /// 解析成功时的API文档，由 save_api 保存
+    document: Option<doc_def::document::ApiDocument>,
+}
 
-    let mut doc_list = Vec::new();
-    for attr_item in fn_item.attrs.iter() {
-        if let Ok(meta) = attr_item.parse_meta() {
-            match meta {
-                Meta::Path(_val) => {
-                    continue;
-                }
-                Meta::List(_meta_list) => {
-                    continue;
-                }
-                Meta::NameValue(val) => {
-                    let token_name = val.path.to_token_stream().to_string();
-                    if token_name.as_str() == "doc" {
-                        if let Lit::Str(val) = &val.lit {
-                            doc_list.push(val.value());
-                        }
-                    }
+/// 解析函数的API文档，返回错误、警告以及API文档。只修改函数的属性，不保存文档
+fn expand_api(
+    mut api_arg: ApiArg,
+    has_attr_arg: bool,
+    attrs: &mut Vec<syn::Attribute>,
+    sig: &syn::Signature,
+    span: proc_macro2::Span,
+) -> ApiExpandResult {
+    let mut result = ApiExpandResult {
+        error_list: Vec::new(),
+        warning_list: Vec::new(),
+        document: None,
+    };
+    let doc_line_list = get_doc_line_list(attrs);
+    let doc_list: Vec<String> = doc_line_list.iter().map(|val| val.0.clone()).collect();
+
+    if doc_list.is_empty() && !has_attr_arg {
+        let err = syn::Error::new(span, "excepted api document");
+        result.error_list.push(err.to_compile_error());
+        return result;
+    }
+
+    api_arg.extra_info.inferred_param_list = infer_fn_params(sig);
+    // 查找参数集合时的问题，如无法解析的源码文件
+    let mut problem_list = Vec::new();
+    let use_keyword = doc_def::doc_params::USE_KEYWORD;
+    if doc_list.iter().any(|val| val.split_whitespace().next() == Some(use_keyword)) {
+        let (param_set_list, list) = param_set::get_param_set_list();
+        api_arg.extra_info.param_set_list = param_set_list;
+        problem_list = list;
+    }
+    let parse_result = doc_def::document::parse_statement_with_extra(doc_list, api_arg.extra_info);
+    let mut warning_list: Vec<(proc_macro2::Span, String)> = problem_list
+        .into_iter()
+        .map(|problem_item| (span, format!("doc_params: {}", problem_item)))
+        .collect();
+    warning_list.extend(parse_result.warning_list.iter().map(|warning_item| {
+        let span = get_line_span(&doc_line_list, warning_item.line_index, span);
+        (span, warning_item.to_string())
+    }));
+    if api_arg.is_check_params && parse_result.is_ok() {
+        warning_list.append(&mut check_fn_params(sig, span, &parse_result, &doc_line_list));
+    }
+    result.warning_list = warning_list;
+
+    if !parse_result.is_ok() {
+        // 所有的错误都定位到出错的那一行注释，并一次性报告
+        for err_item in parse_result.error_list.iter() {
+            let span = get_line_span(&doc_line_list, err_item.line_index, span);
+            result.error_list.push(syn::Error::new(span, err_item.to_string()).to_compile_error());
+        }
+        return result;
+    }
+
+    let mut doc_obj = parse_result.document;
+    // 成功响应未指定返回值时，使用 Json<R> 中的类型做为其返回值类型，没有成功响应时添加一个
+    if let Some(val) = infer_return_type(sig) {
+        match doc_obj.response_list.iter_mut().find(|val| val.is_success()) {
+            Some(response_item) => {
+                if response_item.type_name.is_empty()
+                    && response_item.content.is_empty()
+                    && response_item.field_list.is_empty()
+                {
+                    response_item.type_name = val;
                 }
             }
+            None => doc_obj.response_list.push(doc_def::document::ApiResponse {
+                type_name: val,
+                ..doc_def::document::ApiResponse::default()
+            }),
         }
     }
 
+    // 废弃的API同时添加 #[deprecated]，以便内部调用时也能得到编译警告
+    if doc_obj.deprecated && !attrs.iter().any(|val| val.path.is_ident("deprecated")) {
+        attrs.push(make_deprecated_attr(&doc_obj));
+    }
+
+    result.document = Some(doc_obj);
+    result
+}
+
+/// 检查API是否重复定义并保存API文档，出错时把错误加入解析结果
+fn save_api(expand_result: &mut ApiExpandResult, span: proc_macro2::Span) {
+    let doc_obj = match expand_result.document.as_ref() {
+        Some(val) => val,
+        None => return,
+    };
+
+    let api_key = format!("{}_{}", &doc_obj.module_name, &doc_obj.name);
+    let mut all_api_map = ALL_API.lock().unwrap();
+    if all_api_map.contains_key(&api_key) {
+        let err = syn::Error::new(
+            span,
+            format!(
+                "repeated api define. module:{} fn:{}",
+                &doc_obj.module_name, &doc_obj.name
+            ),
+        );
+        expand_result.error_list.push(err.to_compile_error());
+        return;
+    }
+    all_api_map.insert(api_key, true);
+
+    if let Err(err) = doc_def::file::save_item(doc_def::file::ItemType::Api, doc_obj) {
+        expand_result.error_list.push(syn::Error::new(span, err.as_str()).to_compile_error());
+    }
+}
+
+/// 把错误以及警告放到函数体的最前面。impl以及trait中不能使用 const _ ，所以不能放在函数之后
+fn insert_into_block(block: &mut syn::Block, expand_result: ApiExpandResult) {
+    let warning_list = expand_result
+        .warning_list
+        .iter()
+        .enumerate()
+        .map(|(index, (span, message))| make_warning(*span, index, message));
+    let token_list = expand_result.error_list.into_iter().chain(warning_list);
+    for (index, token_item) in token_list.enumerate() {
+        block
+            .stmts
+            .insert(index, syn::Stmt::Item(syn::Item::Verbatim(token_item)));
+    }
+}
+
+// 用于标记API文档的头部信息
+#[proc_macro_attribute]
+pub fn doc_header(arg: TokenStream, input: TokenStream) -> TokenStream {
+    let arg_list = parse_macro_input!(arg as syn::AttributeArgs);
+    let fn_item = parse_macro_input!(input as syn::ItemFn);
+    if let Some(arg_item) = arg_list.first() {
+        let err = syn::Error::new(arg_item.span(), "doc_header does not accept arguments");
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+
+    let doc_list = get_doc_list(&fn_item.attrs);
+
     let mut is_set_head = DOC_HEADER_IS_SET.lock().unwrap();
     if *is_set_head {
         let err = syn::Error::new(fn_item.span(), "repeated doc header set");
@@ -134,7 +316,7 @@ pub fn doc_header(_arg: TokenStream, input: TokenStream) -> TokenStream {
     }
     *is_set_head = true;
 
-    if doc_list.len() == 0 {
+    if doc_list.is_empty() {
         let err = syn::Error::new(fn_item.span(), "excepted doc header");
         return proc_macro::TokenStream::from(err.to_compile_error());
     }
@@ -151,3 +333,1291 @@ pub fn doc_header(_arg: TokenStream, input: TokenStream) -> TokenStream {
 
     quote!(#fn_item).into()
 }
+
+// 用于标记API模块。记录模块的标题、描述、展示顺序以及请求路径前缀，
+// 模块内的 #[api] 函数会继承模块名以及请求路径前缀
+#[proc_macro_attribute]
+pub fn api_module(arg: TokenStream, input: TokenStream) -> TokenStream {
+    let arg_list = parse_macro_input!(arg as syn::AttributeArgs);
+    let mut mod_item = parse_macro_input!(input as syn::ItemMod);
+
+    let mut module_arg = match parse_module_arg(&arg_list, true) {
+        Ok(val) => val,
+        Err(err) => return proc_macro::TokenStream::from(err.to_compile_error()),
+    };
+    let module_doc = &mut module_arg.module_doc;
+    if module_doc.name.is_empty() {
+        module_doc.name = mod_item.ident.to_string();
+    }
+    if let Some(val) = module_arg.parent_base_path {
+        module_doc.base_path = doc_def::document::join_base_path(&val, &module_doc.base_path);
+    }
+    module_doc.desc = get_doc_desc(&mod_item.attrs);
+
+    let content = match mod_item.content.as_mut() {
+        Some(val) => val,
+        None => {
+            let err = syn::Error::new(
+                mod_item.span(),
+                "api_module only support inline mod, use #[doc_module] for other mod",
+            );
+            return proc_macro::TokenStream::from(err.to_compile_error());
+        }
+    };
+    inject_module_arg(&mut content.1, &module_arg.module_doc);
+
+    if let Err(err) = save_module(&module_arg.module_doc, mod_item.span()) {
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+
+    quote!(#mod_item).into()
+}
+
+// 用于记录API模块的信息，可以标记在任意项上。与 #[api_module] 不同，不会影响其他的API
+#[proc_macro_attribute]
+pub fn doc_module(arg: TokenStream, input: TokenStream) -> TokenStream {
+    let arg_list = parse_macro_input!(arg as syn::AttributeArgs);
+    let item = parse_macro_input!(input as syn::Item);
+
+    let mut module_arg = match parse_module_arg(&arg_list, false) {
+        Ok(val) => val,
+        Err(err) => return proc_macro::TokenStream::from(err.to_compile_error()),
+    };
+    if module_arg.module_doc.name.is_empty() {
+        let err = syn::Error::new(item.span(), "doc_module need name = \"...\"");
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+    if let Some(attrs) = get_item_attrs(&item) {
+        module_arg.module_doc.desc = get_doc_desc(attrs);
+    }
+
+    if let Err(err) = save_module(&module_arg.module_doc, item.span()) {
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+
+    quote!(#item).into()
+}
+
+// 用于定义响应的信封，可以标记在任意项上。项的文档注释中 field 行之后为信封的字段，
+// API的响应会做为数据字段的内容。如 #[doc_envelope(name = "Result", data = "Data", default)]
+#[proc_macro_attribute]
+pub fn doc_envelope(arg: TokenStream, input: TokenStream) -> TokenStream {
+    let arg_list = parse_macro_input!(arg as syn::AttributeArgs);
+    let item = parse_macro_input!(input as syn::Item);
+
+    let envelope_arg = match parse_envelope_arg(&arg_list) {
+        Ok(val) => val,
+        Err(err) => return proc_macro::TokenStream::from(err.to_compile_error()),
+    };
+    if envelope_arg.name.is_empty() {
+        let err = syn::Error::new(item.span(), "doc_envelope need name = \"...\"");
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+
+    let doc_line_list = match get_item_attrs(&item) {
+        Some(attrs) => get_doc_line_list(attrs),
+        None => Vec::new(),
+    };
+    let doc_list: Vec<String> = doc_line_list.iter().map(|val| val.0.clone()).collect();
+    let parse_result = doc_def::doc_envelope::parse_envelope(&doc_list, &envelope_arg.data_field);
+    if !parse_result.error_list.is_empty() {
+        let error_list = parse_result.error_list.iter().map(|err_item| {
+            let span = get_line_span(&doc_line_list, err_item.line_index, item.span());
+            syn::Error::new(span, err_item.to_string()).to_compile_error()
+        });
+        return quote!(#(#error_list)* #item).into();
+    }
+    let warning_list = parse_result.warning_list.iter().enumerate().map(|(index, warning_item)| {
+        let span = get_line_span(&doc_line_list, warning_item.line_index, item.span());
+        make_warning(span, index, &warning_item.to_string())
+    });
+    let warning_list: Vec<proc_macro2::TokenStream> = warning_list.collect();
+
+    let mut envelope_doc = parse_result.envelope;
+    envelope_doc.name = envelope_arg.name;
+    envelope_doc.is_default = envelope_arg.is_default;
+
+    let mut all_envelope_map = ALL_ENVELOPE.lock().unwrap();
+    if all_envelope_map.contains_key(&envelope_doc.name) {
+        let err = syn::Error::new(
+            item.span(),
+            format!("repeated envelope define. envelope:{}", &envelope_doc.name),
+        );
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+    if envelope_doc.is_default {
+        let mut default_envelope = DEFAULT_ENVELOPE.lock().unwrap();
+        if let Some(val) = default_envelope.as_ref() {
+            let err = syn::Error::new(item.span(), format!("repeated default envelope, '{}' is already default", val));
+            return proc_macro::TokenStream::from(err.to_compile_error());
+        }
+        *default_envelope = Some(envelope_doc.name.clone());
+    }
+    all_envelope_map.insert(envelope_doc.name.clone(), true);
+
+    if let Err(err) = doc_def::file::save_item(doc_def::file::ItemType::Envelope, &envelope_doc) {
+        let err = syn::Error::new(item.span(), err.as_str());
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+
+    quote!(#item #(#warning_list)*).into()
+}
+
+// 用于定义可以复用的参数集合，可以标记在任意项上。项的文档注释中每一行为一个参数，
+// API的 param 段中使用 use {参数集合名} 引用。如 #[doc_params(name = "auth")]
+#[proc_macro_attribute]
+pub fn doc_params(arg: TokenStream, input: TokenStream) -> TokenStream {
+    let arg_list = parse_macro_input!(arg as syn::AttributeArgs);
+    let item = parse_macro_input!(input as syn::Item);
+
+    let name = match param_set::parse_param_set_arg(&arg_list, item.span()) {
+        Ok(val) => val,
+        Err(err) => return proc_macro::TokenStream::from(err.to_compile_error()),
+    };
+
+    let doc_line_list = match get_item_attrs(&item) {
+        Some(attrs) => get_doc_line_list(attrs),
+        None => Vec::new(),
+    };
+    let doc_list: Vec<String> = doc_line_list.iter().map(|val| val.0.clone()).collect();
+    let parse_result = doc_def::doc_params::parse_param_set(&doc_list);
+    if !parse_result.error_list.is_empty() {
+        let error_list = parse_result.error_list.iter().map(|err_item| {
+            let span = get_line_span(&doc_line_list, err_item.line_index, item.span());
+            syn::Error::new(span, err_item.to_string()).to_compile_error()
+        });
+        return quote!(#(#error_list)* #item).into();
+    }
+    if parse_result.param_list.is_empty() {
+        let err = syn::Error::new(item.span(), format!("param set '{}' has no param", &name));
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+    let warning_list = parse_result.warning_list.iter().enumerate().map(|(index, warning_item)| {
+        let span = get_line_span(&doc_line_list, warning_item.line_index, item.span());
+        make_warning(span, index, &warning_item.to_string())
+    });
+    let warning_list: Vec<proc_macro2::TokenStream> = warning_list.collect();
+
+    // 参数集合只在编译时使用，不保存到文档数据中
+    let param_set = doc_def::doc_params::ParamSetDocument {
+        name: name.clone(),
+        param_list: parse_result.param_list,
+    };
+    if !param_set::register_param_set(param_set) {
+        let err = syn::Error::new(item.span(), format!("repeated param set define. param set:{}", &name));
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+
+    quote!(#item #(#warning_list)*).into()
+}
+
+// 用于标记API中使用的类型。会记录struct的字段信息，以便在 return type {TypeName} 中引用
+#[proc_macro_attribute]
+pub fn doc_type(_arg: TokenStream, input: TokenStream) -> TokenStream {
+    let struct_item = parse_macro_input!(input as syn::ItemStruct);
+
+    let fields = match &struct_item.fields {
+        syn::Fields::Named(val) => val,
+        _ => {
+            let err = syn::Error::new(
+                struct_item.span(),
+                "doc_type only support struct with named fields",
+            );
+            return proc_macro::TokenStream::from(err.to_compile_error());
+        }
+    };
+
+    let mut type_doc = doc_def::doc_type::TypeDocument {
+        name: struct_item.ident.to_string(),
+        module_path: get_module_path(),
+        desc: get_doc_desc(&struct_item.attrs),
+        field_list: Vec::new(),
+    };
+    let rename_all = get_serde_rename_all(&struct_item.attrs);
+    for field_item in fields.named.iter() {
+        let serde_info = get_serde_field_info(&field_item.attrs);
+        if serde_info.skip {
+            continue;
+        }
+
+        let field_name = match serde_info.rename {
+            Some(val) => val,
+            None => {
+                let field_name = field_item.ident.as_ref().unwrap().to_string();
+                let field_name = field_name.trim_start_matches("r#");
+                match &rename_all {
+                    Some(rule) => apply_rename_all(rule, field_name),
+                    None => field_name.to_string(),
+                }
+            }
+        };
+        type_doc.field_list.push(doc_def::doc_type::TypeField {
+            name: field_name,
+            field_type: type_to_string(&field_item.ty),
+            optional: is_option_type(&field_item.ty),
+            desc: get_doc_desc(&field_item.attrs),
+        });
+    }
+
+    let mut all_type_map = ALL_TYPE.lock().unwrap();
+    let full_name = type_doc.full_name();
+    if all_type_map.contains_key(&full_name) {
+        let err = syn::Error::new(
+            struct_item.ident.span(),
+            format!("repeated doc type define. type:{}", &full_name),
+        );
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+    all_type_map.insert(full_name, true);
+
+    let result = doc_def::file::save_item(doc_def::file::ItemType::Type, &type_doc);
+    if let Err(err) = result {
+        let err = syn::Error::new(struct_item.span(), err.as_str());
+        return proc_macro::TokenStream::from(err.to_compile_error());
+    }
+
+    quote!(#struct_item).into()
+}
+
+/// 获取所有的文档注释行
+fn get_doc_list(attrs: &[syn::Attribute]) -> Vec<String> {
+    get_doc_line_list(attrs)
+        .into_iter()
+        .map(|val| val.0)
+        .collect()
+}
+
+/// 获取所有的文档注释行以及每一行对应的 #[doc = "..."] 的位置
+fn get_doc_line_list(attrs: &[syn::Attribute]) -> Vec<(String, proc_macro2::Span)> {
+    let mut doc_list = Vec::new();
+    for attr_item in attrs.iter() {
+        if let Ok(Meta::NameValue(val)) = attr_item.parse_meta() {
+            let token_name = val.path.to_token_stream().to_string();
+            if token_name.as_str() == "doc" {
+                if let Lit::Str(val) = &val.lit {
+                    doc_list.push((val.value(), attr_item.span()));
+                }
+            }
+        }
+    }
+
+    doc_list
+}
+
+/// 获取注释行对应的位置，找不到时使用默认位置
+fn get_line_span(
+    doc_line_list: &[(String, proc_macro2::Span)],
+    line_index: usize,
+    default_span: proc_macro2::Span,
+) -> proc_macro2::Span {
+    match doc_line_list.get(line_index) {
+        Some(val) => val.1,
+        None => default_span,
+    }
+}
+
+/// 生成一个编译警告。稳定版的过程宏不能直接输出警告，所以借助使用废弃常量时产生的警告
+fn make_warning(span: proc_macro2::Span, index: usize, message: &str) -> proc_macro2::TokenStream {
+    let name = syn::Ident::new(&format!("api_doc_warning_{}", index), span);
+    quote_spanned!(span=>
+        const _: () = {
+            #[deprecated(note = #message)]
+            #[allow(non_upper_case_globals)]
+            const #name: () = ();
+            #name
+        };
+    )
+}
+
+/// #[api(...)] 的属性参数
+struct ApiArg {
+    extra_info: doc_def::document::ApiExtraInfo,
+    /// 是否检查文档中的参数与函数参数是否一致。由 check_params 指定
+    is_check_params: bool,
+}
+
+/// 解析 #[api(...)] 的属性参数，如 #[api(module = "User", path = "/v1/user", method = "get")]
+fn parse_api_arg(arg_list: &[NestedMeta]) -> Result<ApiArg, syn::Error> {
+    let mut result = ApiArg {
+        extra_info: doc_def::document::ApiExtraInfo::default(),
+        is_check_params: false,
+    };
+
+    // 由 #[api_module] 添加的请求路径前缀，base_path = "..." 优先
+    let mut parent_base_path = None;
+    let mut exist_list: Vec<String> = Vec::new();
+    for arg_item in arg_list {
+        let meta = match arg_item {
+            NestedMeta::Meta(val) => val,
+            NestedMeta::Lit(_) => {
+                return Err(syn::Error::new(arg_item.span(), "expected key = \"value\""));
+            }
+        };
+        let key = match meta.path().get_ident() {
+            Some(val) => val.to_string(),
+            None => String::new(),
+        };
+        if exist_list.contains(&key) {
+            return Err(syn::Error::new(arg_item.span(), format!("repeated api argument '{}'", key)));
+        }
+        exist_list.push(key.clone());
+
+        match (key.as_str(), meta) {
+            ("check_params", Meta::Path(_)) => result.is_check_params = true,
+            ("deprecated", Meta::Path(_)) => result.extra_info.deprecated = Some(true),
+            ("deprecated", Meta::NameValue(val)) => match &val.lit {
+                Lit::Bool(lit) => result.extra_info.deprecated = Some(lit.value),
+                _ => return Err(syn::Error::new(val.lit.span(), "expected true or false")),
+            },
+            ("base_path", Meta::NameValue(val)) | ("parent_base_path", Meta::NameValue(val)) => {
+                let value = match &val.lit {
+                    Lit::Str(lit) => lit.value().trim().to_string(),
+                    _ => return Err(syn::Error::new(val.lit.span(), "expected string literal")),
+                };
+                if key == "base_path" {
+                    result.extra_info.base_path = Some(value);
+                } else {
+                    parent_base_path = Some(value);
+                }
+            }
+            ("module", Meta::NameValue(val))
+            | ("parent_module", Meta::NameValue(val))
+            | ("name", Meta::NameValue(val))
+            | ("path", Meta::NameValue(val))
+            | ("method", Meta::NameValue(val))
+            | ("tags", Meta::NameValue(val)) => {
+                let value = match &val.lit {
+                    Lit::Str(lit) => lit.value().trim().to_string(),
+                    _ => return Err(syn::Error::new(val.lit.span(), "expected string literal")),
+                };
+                if value.is_empty() {
+                    return Err(syn::Error::new(val.lit.span(), format!("{} can not be empty", key)));
+                }
+                let extra_info = &mut result.extra_info;
+                match key.as_str() {
+                    "module" => extra_info.module_name = Some(value),
+                    "parent_module" => extra_info.parent_module = Some(value),
+                    "name" => extra_info.name = Some(value),
+                    "path" => extra_info.path = Some(value),
+                    "method" => extra_info.http_method = Some(value),
+                    _ => {
+                        let mut tag_list: Vec<String> = Vec::new();
+                        for tag_item in value.split([',', '，']).map(|val| val.trim()) {
+                            if !tag_item.is_empty() && !tag_list.iter().any(|val| val == tag_item) {
+                                tag_list.push(tag_item.to_string());
+                            }
+                        }
+                        extra_info.tag_list = Some(tag_list);
+                    }
+                }
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    arg_item.span(),
+                    "unknown api argument, expected one of module, name, path, method, tags, deprecated, base_path, check_params",
+                ));
+            }
+        }
+    }
+    if result.extra_info.base_path.is_none() {
+        result.extra_info.base_path = parent_base_path;
+    }
+
+    Ok(result)
+}
+
+/// #[doc_envelope(...)] 的属性参数
+struct EnvelopeArg {
+    name: String,
+    /// 数据字段名。由 data = "..." 指定，默认为 Data
+    data_field: String,
+    /// 是否是默认信封。由 default 指定
+    is_default: bool,
+}
+
+/// 解析信封的属性参数，如 #[doc_envelope(name = "Result", data = "Data", default)]
+fn parse_envelope_arg(arg_list: &[NestedMeta]) -> Result<EnvelopeArg, syn::Error> {
+    let mut result = EnvelopeArg {
+        name: String::new(),
+        data_field: doc_def::doc_envelope::DEFAULT_DATA_FIELD.to_string(),
+        is_default: false,
+    };
+
+    let mut exist_list: Vec<String> = Vec::new();
+    for arg_item in arg_list {
+        let meta = match arg_item {
+            NestedMeta::Meta(val) => val,
+            NestedMeta::Lit(_) => {
+                return Err(syn::Error::new(arg_item.span(), "expected key = \"value\""));
+            }
+        };
+        let key = match meta.path().get_ident() {
+            Some(val) => val.to_string(),
+            None => String::new(),
+        };
+        if exist_list.contains(&key) {
+            return Err(syn::Error::new(arg_item.span(), format!("repeated envelope argument '{}'", key)));
+        }
+        exist_list.push(key.clone());
+
+        match (key.as_str(), meta) {
+            ("default", Meta::Path(_)) => result.is_default = true,
+            ("name", Meta::NameValue(val)) | ("data", Meta::NameValue(val)) => {
+                let value = match &val.lit {
+                    Lit::Str(lit) => lit.value().trim().to_string(),
+                    _ => return Err(syn::Error::new(val.lit.span(), "expected string literal")),
+                };
+                if value.is_empty() {
+                    return Err(syn::Error::new(val.lit.span(), format!("{} can not be empty", key)));
+                }
+                if key == "name" && value == doc_def::doc_envelope::NO_ENVELOPE {
+                    return Err(syn::Error::new(val.lit.span(), format!("'{}' is reserved", value)));
+                }
+                if key == "name" {
+                    result.name = value;
+                } else {
+                    result.data_field = value;
+                }
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    arg_item.span(),
+                    "unknown envelope argument, expected one of name, data, default",
+                ));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// #[api_module(...)] 与 #[doc_module(...)] 的属性参数
+struct ModuleArg {
+    module_doc: doc_def::doc_module::ModuleDocument,
+    /// 外层 #[api_module] 的请求路径前缀
+    parent_base_path: Option<String>,
+}
+
+/// 解析模块的属性参数，如 #[api_module(name = "User", title = "用户", order = 1, base_path = "/v1/user")]
+fn parse_module_arg(arg_list: &[NestedMeta], is_support_base_path: bool) -> Result<ModuleArg, syn::Error> {
+    let mut result = ModuleArg {
+        module_doc: doc_def::doc_module::ModuleDocument::default(),
+        parent_base_path: None,
+    };
+
+    let mut exist_list: Vec<String> = Vec::new();
+    for arg_item in arg_list {
+        let name_value = match arg_item {
+            NestedMeta::Meta(Meta::NameValue(val)) => val,
+            _ => return Err(syn::Error::new(arg_item.span(), "expected key = value")),
+        };
+        let key = match name_value.path.get_ident() {
+            Some(val) => val.to_string(),
+            None => String::new(),
+        };
+        if exist_list.contains(&key) {
+            return Err(syn::Error::new(arg_item.span(), format!("repeated module argument '{}'", key)));
+        }
+        exist_list.push(key.clone());
+
+        let lit = &name_value.lit;
+        let get_str = || match lit {
+            Lit::Str(val) => Ok(val.value().trim().to_string()),
+            _ => Err(syn::Error::new(lit.span(), "expected string literal")),
+        };
+        let module_doc = &mut result.module_doc;
+        match key.as_str() {
+            "order" => match lit {
+                Lit::Int(val) => module_doc.order = Some(val.base10_parse()?),
+                _ => return Err(syn::Error::new(lit.span(), "expected integer")),
+            },
+            "name" => module_doc.name = get_str()?,
+            "title" => module_doc.title = get_str()?,
+            "base_path" if is_support_base_path => module_doc.base_path = get_str()?,
+            "parent_base_path" if is_support_base_path => result.parent_base_path = Some(get_str()?),
+            _ => {
+                let expected = if is_support_base_path {
+                    "name, title, order, base_path"
+                } else {
+                    "name, title, order"
+                };
+                return Err(syn::Error::new(
+                    arg_item.span(),
+                    format!("unknown module argument, expected one of {}", expected),
+                ));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// 保存模块文档，同名的模块只能定义一次
+fn save_module(module_doc: &doc_def::doc_module::ModuleDocument, span: proc_macro2::Span) -> Result<(), syn::Error> {
+    let mut all_module_map = ALL_MODULE.lock().unwrap();
+    if all_module_map.contains_key(&module_doc.name) {
+        return Err(syn::Error::new(
+            span,
+            format!("repeated module define. module:{}", &module_doc.name),
+        ));
+    }
+    all_module_map.insert(module_doc.name.clone(), true);
+
+    doc_def::file::save_item(doc_def::file::ItemType::Module, module_doc).map_err(|err| syn::Error::new(span, err))
+}
+
+/// 获取项的属性列表
+fn get_item_attrs(item: &syn::Item) -> Option<&Vec<syn::Attribute>> {
+    match item {
+        syn::Item::Const(val) => Some(&val.attrs),
+        syn::Item::Enum(val) => Some(&val.attrs),
+        syn::Item::Fn(val) => Some(&val.attrs),
+        syn::Item::Mod(val) => Some(&val.attrs),
+        syn::Item::Static(val) => Some(&val.attrs),
+        syn::Item::Struct(val) => Some(&val.attrs),
+        syn::Item::Trait(val) => Some(&val.attrs),
+        syn::Item::Type(val) => Some(&val.attrs),
+        syn::Item::Union(val) => Some(&val.attrs),
+        syn::Item::Use(val) => Some(&val.attrs),
+        _ => None,
+    }
+}
+
+/// 为模块内的 #[api] 以及 #[api_module] 添加外层模块的信息。
+/// impl、trait中的方法以及不带属性的子mod会继续向内查找
+fn inject_module_arg(item_list: &mut [syn::Item], module_doc: &doc_def::doc_module::ModuleDocument) {
+    let module_name = &module_doc.name;
+    let base_path = &module_doc.base_path;
+    let api_arg = quote!(parent_module = #module_name, parent_base_path = #base_path);
+    let inject_api_arg = |attrs: &mut Vec<syn::Attribute>| {
+        let mut is_api = false;
+        for attr in attrs.iter_mut() {
+            if is_macro_attr(attr, "api") {
+                is_api = true;
+                append_attr_arg(attr, api_arg.clone());
+            }
+        }
+        is_api
+    };
+
+    for item in item_list.iter_mut() {
+        match item {
+            syn::Item::Fn(val) => {
+                inject_api_arg(&mut val.attrs);
+            }
+            syn::Item::Impl(val) => {
+                if inject_api_arg(&mut val.attrs) {
+                    continue;
+                }
+                for impl_item in val.items.iter_mut() {
+                    if let syn::ImplItem::Method(method_item) = impl_item {
+                        inject_api_arg(&mut method_item.attrs);
+                    }
+                }
+            }
+            syn::Item::Trait(val) => {
+                for trait_item in val.items.iter_mut() {
+                    if let syn::TraitItem::Method(method_item) = trait_item {
+                        inject_api_arg(&mut method_item.attrs);
+                    }
+                }
+            }
+            syn::Item::Mod(val) => {
+                let mut is_api_module = false;
+                for attr in val.attrs.iter_mut() {
+                    if is_macro_attr(attr, "api_module") {
+                        is_api_module = true;
+                        append_attr_arg(attr, quote!(parent_base_path = #base_path));
+                    }
+                }
+                if !is_api_module {
+                    if let Some(content) = val.content.as_mut() {
+                        inject_module_arg(&mut content.1, module_doc);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 属性是否是本库的宏，如 #[api]、#[doc_macro::api]
+fn is_macro_attr(attr: &syn::Attribute, name: &str) -> bool {
+    match attr.path.segments.last() {
+        Some(val) => val.ident == name,
+        None => false,
+    }
+}
+
+/// 在属性的参数列表最后添加参数
+fn append_attr_arg(attr: &mut syn::Attribute, arg: proc_macro2::TokenStream) {
+    let old_arg = match syn::parse2::<proc_macro2::Group>(attr.tokens.clone()) {
+        Ok(val) => val.stream(),
+        Err(_) => proc_macro2::TokenStream::new(),
+    };
+    let is_end_with_comma = match old_arg.clone().into_iter().last() {
+        Some(proc_macro2::TokenTree::Punct(val)) => val.as_char() == ',',
+        _ => false,
+    };
+    attr.tokens = if old_arg.is_empty() || is_end_with_comma {
+        quote!((#old_arg #arg))
+    } else {
+        quote!((#old_arg, #arg))
+    };
+}
+
+/// 提取器类型与参数位置的对应关系。支持axum、actix-web以及warp中的同名类型
+const EXTRACTOR_LIST: [(&str, doc_def::document::ParamLocation); 4] = [
+    ("Path", doc_def::document::ParamLocation::Path),
+    ("Query", doc_def::document::ParamLocation::Query),
+    ("Json", doc_def::document::ParamLocation::Body),
+    ("Form", doc_def::document::ParamLocation::Form),
+];
+
+/// 识别 Path<T>、Query<T>、Json<T>、Form<T> 等提取器类型，返回参数位置以及内部的类型
+fn get_extractor(ty: &syn::Type) -> Option<(doc_def::document::ParamLocation, &syn::Type)> {
+    let type_path = match ty {
+        syn::Type::Path(val) => val,
+        _ => return None,
+    };
+    let segment = type_path.path.segments.last()?;
+    let location = EXTRACTOR_LIST
+        .iter()
+        .find(|val| segment.ident == val.0)
+        .map(|val| val.1)?;
+    let arg_list = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(val) => &val.args,
+        _ => return None,
+    };
+    if arg_list.len() != 1 {
+        return None;
+    }
+    match arg_list.first() {
+        Some(syn::GenericArgument::Type(val)) => Some((location, val)),
+        _ => None,
+    }
+}
+
+/// 获取参数模式中的参数名，支持 name 以及 Path(name) 形式
+fn get_pat_name(pat: &syn::Pat) -> Option<String> {
+    match pat {
+        syn::Pat::Ident(val) => Some(val.ident.to_string().trim_start_matches('_').to_string()),
+        syn::Pat::TupleStruct(val) if val.pat.elems.len() == 1 => get_pat_name(&val.pat.elems[0]),
+        _ => None,
+    }
+}
+
+/// 函数参数信息。提取器中的元组会展开为多个参数，如 Path((id, name)): Path<(u32, String)>
+struct FnArgInfo<'a> {
+    /// 参数名。模式无法得到名字时为None
+    name: Option<String>,
+    /// 参数类型。提取器类型为其内部的类型
+    ty: &'a syn::Type,
+    /// 由提取器类型得到的参数位置
+    location: Option<doc_def::document::ParamLocation>,
+    span: proc_macro2::Span,
+}
+
+/// 获取函数的所有参数，不包括self
+fn get_fn_arg_list(sig: &syn::Signature) -> Vec<FnArgInfo<'_>> {
+    let mut result = Vec::new();
+    for input_item in sig.inputs.iter() {
+        let pat_type = match input_item {
+            syn::FnArg::Typed(val) => val,
+            syn::FnArg::Receiver(_) => continue,
+        };
+        let (location, inner_type) = match get_extractor(&pat_type.ty) {
+            Some(val) => val,
+            None => {
+                result.push(FnArgInfo {
+                    name: get_pat_name(&pat_type.pat),
+                    ty: &pat_type.ty,
+                    location: None,
+                    span: pat_type.span(),
+                });
+                continue;
+            }
+        };
+
+        // 元组类型与模式中的名字一一对应
+        if let syn::Type::Tuple(type_tuple) = inner_type {
+            let pat_elem_list = match &*pat_type.pat {
+                syn::Pat::TupleStruct(val) if val.pat.elems.len() == 1 => match &val.pat.elems[0] {
+                    syn::Pat::Tuple(val) => Some(&val.elems),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some(pat_elem_list) = pat_elem_list {
+                if pat_elem_list.len() == type_tuple.elems.len() {
+                    for (pat_item, type_item) in pat_elem_list.iter().zip(type_tuple.elems.iter()) {
+                        result.push(FnArgInfo {
+                            name: get_pat_name(pat_item),
+                            ty: type_item,
+                            location: Some(location),
+                            span: pat_item.span(),
+                        });
+                    }
+                    continue;
+                }
+            }
+        }
+
+        result.push(FnArgInfo {
+            name: get_pat_name(&pat_type.pat),
+            ty: inner_type,
+            location: Some(location),
+            span: pat_type.span(),
+        });
+    }
+
+    result
+}
+
+/// 从函数签名中的提取器类型推断参数。
+/// 基础类型的参数使用参数名，如 Path(id): Path<u32>；
+/// 其他类型做为 param type {TypeName} 处理，类型由 #[doc_type] 标记时其字段会展开为参数
+fn infer_fn_params(sig: &syn::Signature) -> Vec<doc_def::document::ApiParam> {
+    use doc_def::doc_type::{get_primitive_kind, parse_type_ref};
+    use doc_def::document::ApiParam;
+
+    let mut result = Vec::new();
+    for arg_item in get_fn_arg_list(sig) {
+        let location = match arg_item.location {
+            Some(val) => val,
+            None => continue,
+        };
+        let type_name = type_to_string(arg_item.ty);
+        let type_ref = parse_type_ref(&type_name);
+        if get_primitive_kind(&type_ref.name).is_none() || type_ref.is_array {
+            result.push(ApiParam {
+                param_type: type_name,
+                required: true,
+                flatten: true,
+                location: Some(location),
+                ..ApiParam::default()
+            });
+        } else if let Some(name) = arg_item.name {
+            result.push(ApiParam {
+                name,
+                param_type: type_name,
+                required: !is_option_type(arg_item.ty),
+                location: Some(location),
+                ..ApiParam::default()
+            });
+        }
+    }
+
+    result
+}
+
+/// 从函数返回值推断返回值类型，支持 Json<R> 以及 Result<Json<R>, E>
+fn infer_return_type(sig: &syn::Signature) -> Option<String> {
+    let mut return_type = match &sig.output {
+        syn::ReturnType::Type(_, val) => &**val,
+        syn::ReturnType::Default => return None,
+    };
+
+    if let syn::Type::Path(type_path) = return_type {
+        let segment = type_path.path.segments.last()?;
+        if segment.ident == "Result" {
+            if let syn::PathArguments::AngleBracketed(val) = &segment.arguments {
+                if let Some(syn::GenericArgument::Type(val)) = val.args.first() {
+                    return_type = val;
+                }
+            }
+        }
+    }
+
+    match get_extractor(return_type) {
+        Some((doc_def::document::ParamLocation::Body, val)) => Some(type_to_string(val)),
+        _ => None,
+    }
+}
+
+/// 检查文档中的参数与函数参数是否一致，返回所有的警告。
+/// 参数名比较时忽略大小写以及 _、- ，基础类型的参数还会比较类型
+fn check_fn_params(
+    sig: &syn::Signature,
+    span: proc_macro2::Span,
+    parse_result: &doc_def::document::ParseResult,
+    doc_line_list: &[(String, proc_macro2::Span)],
+) -> Vec<(proc_macro2::Span, String)> {
+    use doc_def::doc_type::{get_primitive_kind, parse_type_ref};
+    use doc_def::document::{normalize_param_name, ParamLocation};
+
+    let param_list = &parse_result.document.param_list;
+    let is_body_documented = parse_result.document.request_body.is_some();
+    let mut is_matched_list = vec![false; param_list.len()];
+    let mut result = Vec::new();
+    for arg_item in get_fn_arg_list(sig) {
+        // 请求体参数已由 body 段描述
+        if is_body_documented && matches!(arg_item.location, Some(ParamLocation::Body | ParamLocation::Form)) {
+            continue;
+        }
+        let arg_type = type_to_string(arg_item.ty);
+        let arg_type_ref = parse_type_ref(&arg_type);
+        let arg_name = arg_item.name.unwrap_or_else(|| arg_type.clone());
+
+        // 先按参数名查找，再按 param type {TypeName} 的类型查找
+        let param_index = param_list
+            .iter()
+            .position(|val| !val.flatten && normalize_param_name(&val.name) == normalize_param_name(&arg_name))
+            .or_else(|| {
+                param_list
+                    .iter()
+                    .position(|val| val.flatten && parse_type_ref(&val.param_type).name == arg_type_ref.name)
+            });
+        let param_index = match param_index {
+            Some(val) => val,
+            None => {
+                result.push((
+                    arg_item.span,
+                    format!("argument '{}' has no documented param", &arg_name),
+                ));
+                continue;
+            }
+        };
+        is_matched_list[param_index] = true;
+
+        let param_item = &param_list[param_index];
+        if param_item.flatten || arg_type_ref.is_array {
+            continue;
+        }
+        if let (Some(doc_kind), Some(arg_kind)) = (
+            get_primitive_kind(&param_item.param_type),
+            get_primitive_kind(&arg_type_ref.name),
+        ) {
+            if !doc_kind.is_compatible(arg_kind) {
+                result.push((
+                    arg_item.ty.span(),
+                    format!(
+                        "param '{}' is documented as '{}', but argument '{}' is '{}'",
+                        &param_item.name, &param_item.param_type, &arg_name, &arg_type
+                    ),
+                ));
+            }
+        }
+    }
+
+    for (index, param_item) in param_list.iter().enumerate() {
+        if is_matched_list[index] {
+            continue;
+        }
+        let line_index = parse_result.param_line_list.get(index).copied().unwrap_or(0);
+        let name = if param_item.flatten {
+            &param_item.param_type
+        } else {
+            &param_item.name
+        };
+        result.push((
+            get_line_span(doc_line_list, line_index, span),
+            format!("documented param '{}' has no matching argument", name),
+        ));
+    }
+
+    result
+}
+
+/// 根据API的废弃信息生成 #[deprecated] 属性
+fn make_deprecated_attr(doc_obj: &doc_def::document::ApiDocument) -> syn::Attribute {
+    let mut arg_list = Vec::new();
+    if !doc_obj.deprecated_since.is_empty() {
+        let since = &doc_obj.deprecated_since;
+        arg_list.push(quote!(since = #since));
+    }
+    if !doc_obj.replacement.is_empty() {
+        let note = format!("use {} instead", &doc_obj.replacement);
+        arg_list.push(quote!(note = #note));
+    }
+
+    if arg_list.is_empty() {
+        syn::parse_quote!(#[deprecated])
+    } else {
+        syn::parse_quote!(#[deprecated(#(#arg_list),*)])
+    }
+}
+
+/// 获取文档注释做为描述，多行使用\r\n连接
+fn get_doc_desc(attrs: &[syn::Attribute]) -> String {
+    let doc_list: Vec<String> = get_doc_list(attrs)
+        .iter()
+        .map(|val| val.trim().to_string())
+        .collect();
+    doc_list.join("\r\n").trim().to_string()
+}
+
+/// 获取调用宏的源文件对应的模块路径，如 model::user 。
+/// 过程宏中无法使用 module_path!()，所以按源文件相对于编译目标根文件的位置推断，
+/// 不能识别 #[path] 以及文件中的内联模块。找不到源文件时为空
+fn get_module_path() -> String {
+    let file = match proc_macro::Span::call_site().local_file() {
+        Some(val) => val,
+        None => return String::new(),
+    };
+    let root = match std::env::var("CARGO_MANIFEST_DIR") {
+        Ok(dir) => param_set::get_target_root(std::path::Path::new(&dir)),
+        Err(_) => None,
+    };
+    match root {
+        // 编译器给出的文件路径可能是相对于工作目录的
+        Some(root) => match std::env::current_dir() {
+            Ok(dir) => file_to_module_path(&root, &dir.join(file)),
+            Err(_) => String::new(),
+        },
+        None => String::new(),
+    }
+}
+
+/// 按 foo.rs 与 foo/mod.rs 的规则，把源文件路径转换为相对于根文件的模块路径
+fn file_to_module_path(root: &std::path::Path, file: &std::path::Path) -> String {
+    let canonicalize = |path: &std::path::Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let root = canonicalize(root);
+    let file = canonicalize(file);
+    if file == root {
+        return String::new();
+    }
+    let relative = match root.parent().and_then(|dir| file.strip_prefix(dir).ok()) {
+        Some(val) => val.with_extension(""),
+        None => return String::new(),
+    };
+
+    let mut name_list: Vec<String> = relative
+        .components()
+        .map(|val| val.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if name_list.last().map(|val| val.as_str()) == Some("mod") {
+        name_list.pop();
+    }
+    name_list.join("::")
+}
+
+/// 获取struct上 #[serde(rename_all = "...")] 指定的字段重命名规则
+fn get_serde_rename_all(attrs: &[syn::Attribute]) -> Option<String> {
+    let mut result = None;
+    for attr_item in attrs.iter() {
+        if !attr_item.path.is_ident("serde") {
+            continue;
+        }
+        if let Ok(Meta::List(meta_list)) = attr_item.parse_meta() {
+            for nested_item in meta_list.nested.iter() {
+                if let syn::NestedMeta::Meta(val) = nested_item {
+                    if val.path().is_ident("rename_all") {
+                        result = get_serialize_name(val).or(result);
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// 获取 rename = "..." 或 rename(serialize = "...") 中序列化时使用的名字
+fn get_serialize_name(meta: &Meta) -> Option<String> {
+    match meta {
+        Meta::NameValue(val) => match &val.lit {
+            Lit::Str(val) => Some(val.value()),
+            _ => None,
+        },
+        Meta::List(val) => val.nested.iter().find_map(|nested_item| match nested_item {
+            syn::NestedMeta::Meta(Meta::NameValue(val)) if val.path.is_ident("serialize") => match &val.lit {
+                Lit::Str(val) => Some(val.value()),
+                _ => None,
+            },
+            _ => None,
+        }),
+        Meta::Path(_) => None,
+    }
+}
+
+/// 按serde的 rename_all 规则转换字段名。字段名为snake_case，不支持的规则保持原样
+fn apply_rename_all(rule: &str, field_name: &str) -> String {
+    let pascal_name = || {
+        field_name
+            .split('_')
+            .map(|word| {
+                let mut char_list = word.chars();
+                match char_list.next() {
+                    Some(val) => val.to_ascii_uppercase().to_string() + char_list.as_str(),
+                    None => String::new(),
+                }
+            })
+            .collect::<String>()
+    };
+    match rule {
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field_name.to_ascii_uppercase(),
+        "PascalCase" => pascal_name(),
+        "camelCase" => {
+            let name = pascal_name();
+            let mut char_list = name.chars();
+            match char_list.next() {
+                Some(val) => val.to_ascii_lowercase().to_string() + char_list.as_str(),
+                None => name,
+            }
+        }
+        "kebab-case" => field_name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field_name.to_ascii_uppercase().replace('_', "-"),
+        _ => field_name.to_string(),
+    }
+}
+
+/// 字段上的serde配置
+#[derive(Default)]
+struct SerdeFieldInfo {
+    rename: Option<String>,
+    skip: bool,
+}
+
+/// 获取字段上与序列化结果相关的serde配置
+fn get_serde_field_info(attrs: &[syn::Attribute]) -> SerdeFieldInfo {
+    let mut result = SerdeFieldInfo::default();
+    for attr_item in attrs.iter() {
+        if !attr_item.path.is_ident("serde") {
+            continue;
+        }
+        let meta_list = match attr_item.parse_meta() {
+            Ok(Meta::List(val)) => val,
+            _ => continue,
+        };
+        for nested_item in meta_list.nested.iter() {
+            match nested_item {
+                syn::NestedMeta::Meta(val) if val.path().is_ident("rename") => {
+                    if let Some(val) = get_serialize_name(val) {
+                        result.rename = Some(val);
+                    }
+                }
+                syn::NestedMeta::Meta(Meta::Path(val))
+                    if val.is_ident("skip") || val.is_ident("skip_serializing") =>
+                {
+                    result.skip = true;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    result
+}
+
+/// 类型是否是Option
+fn is_option_type(ty: &syn::Type) -> bool {
+    if let syn::Type::Path(val) = ty {
+        if let Some(segment) = val.path.segments.last() {
+            return segment.ident == "Option";
+        }
+    }
+
+    false
+}
+
+/// 把类型转换为紧凑的字符串，如 Option < Vec < String > > 转换为 Option<Vec<String>>
+fn type_to_string(ty: &syn::Type) -> String {
+    let source = ty.to_token_stream().to_string();
+    let char_list: Vec<char> = source.chars().collect();
+    let is_word_char = |val: char| val.is_alphanumeric() || val == '_';
+
+    let mut result = String::new();
+    for (index, char_item) in char_list.iter().enumerate() {
+        if *char_item == ' ' {
+            // 仅保留两个单词之间的空格，如 dyn Trait
+            let prev_is_word = index > 0 && is_word_char(char_list[index - 1]);
+            let next_is_word = index + 1 < char_list.len() && is_word_char(char_list[index + 1]);
+            if !(prev_is_word && next_is_word) {
+                continue;
+            }
+        }
+        result.push(*char_item);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        apply_rename_all, check_fn_params, expand_api_impl, expand_api_trait_method, file_to_module_path,
+        get_serde_field_info, get_serde_rename_all, infer_fn_params, infer_return_type, parse_api_arg, ApiExpandResult,
+    };
+    use doc_def::document::{parse_statement_with_extra, ApiExtraInfo, ParamLocation};
+    use std::path::Path;
+
+    /// 测试中不保存API文档，以免写入 doc.data 以及记录到重复检查中
+    fn save_nothing(_expand_result: &mut ApiExpandResult, _span: proc_macro2::Span) {}
+
+    /// 解析 #[api(...)] 的属性参数
+    fn make_api_arg(arg_list: Vec<syn::NestedMeta>) -> crate::ApiArg {
+        parse_api_arg(&arg_list).unwrap()
+    }
+
+    #[test]
+    pub fn test_infer_fn_params() {
+        let sig: syn::Signature = syn::parse_quote! {
+            fn get_user(
+                Path((id, name)): Path<(u32, String)>,
+                Query(page): Query<Option<u32>>,
+                Json(body): Json<UserInfo>,
+                Form(id_list): axum::extract::Form<Vec<u32>>,
+                state: State,
+            )
+        };
+        let param_list = infer_fn_params(&sig);
+        assert_eq!(param_list.len(), 5);
+        assert_eq!(param_list[0].name, "id");
+        assert_eq!(param_list[0].param_type, "u32");
+        assert_eq!(param_list[0].location, Some(ParamLocation::Path));
+        assert_eq!(param_list[1].name, "name");
+        assert_eq!(param_list[2].name, "page");
+        assert_eq!(param_list[2].location, Some(ParamLocation::Query));
+        assert!(!param_list[2].required);
+        // 不是基础类型时按 param type {TypeName} 处理
+        assert!(param_list[3].flatten);
+        assert_eq!(param_list[3].param_type, "UserInfo");
+        assert_eq!(param_list[3].location, Some(ParamLocation::Body));
+        assert!(param_list[4].flatten);
+        assert_eq!(param_list[4].param_type, "Vec<u32>");
+        assert_eq!(param_list[4].location, Some(ParamLocation::Form));
+    }
+
+    #[test]
+    pub fn test_infer_return_type() {
+        let infer = |sig: syn::Signature| infer_return_type(&sig);
+        assert_eq!(infer(syn::parse_quote!(fn f() -> Json<UserInfo>)).as_deref(), Some("UserInfo"));
+        assert_eq!(
+            infer(syn::parse_quote!(fn f() -> Result<Json<Vec<UserInfo>>, Error>)).as_deref(),
+            Some("Vec<UserInfo>")
+        );
+        assert_eq!(infer(syn::parse_quote!(fn f() -> axum::Json<UserInfo>)).as_deref(), Some("UserInfo"));
+        assert_eq!(infer(syn::parse_quote!(fn f() -> Result<String, Error>)), None);
+        assert_eq!(infer(syn::parse_quote!(fn f() -> Query<UserInfo>)), None);
+        assert_eq!(infer(syn::parse_quote!(fn f() -> String)), None);
+        assert_eq!(infer(syn::parse_quote!(fn f())), None);
+    }
+
+    #[test]
+    pub fn test_check_fn_params() {
+        let sig: syn::Signature = syn::parse_quote! {
+            fn check_user(Path(id): Path<u32>, Query(page): Query<u32>, Json(body): Json<UserInfo>, token: String)
+        };
+        let doc_list = [
+            " module User",
+            " fn CheckUser /v1/CheckUser/{id} post",
+            " param",
+            "     Id string in:path required 用户id",
+            "     Extra int required",
+        ];
+        let extra_info = ApiExtraInfo {
+            inferred_param_list: infer_fn_params(&sig),
+            ..ApiExtraInfo::default()
+        };
+        let parse_result =
+            parse_statement_with_extra(doc_list.iter().map(|val| val.to_string()).collect(), extra_info);
+        assert!(parse_result.is_ok(), "{:?}", parse_result.error_list);
+
+        let span = proc_macro2::Span::call_site();
+        let warning_list: Vec<String> = check_fn_params(&sig, span, &parse_result, &[])
+            .into_iter()
+            .map(|val| val.1)
+            .collect();
+        // 推断的 page 与 Json<UserInfo> 都有对应的参数
+        assert_eq!(
+            warning_list,
+            vec![
+                "param 'Id' is documented as 'string', but argument 'id' is 'u32'",
+                "argument 'token' has no documented param",
+                "documented param 'Extra' has no matching argument",
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_expand_api_impl() {
+        let impl_item: syn::ItemImpl = syn::parse_quote! {
+            impl UserService {
+                /// fn ImplGetUser /v1/ImplGetUser post
+                /// param
+                ///     Id int
+                fn get_user(&self, id: u64, name: String) {}
+
+                /// 不是API
+                fn helper(&self) {}
+            }
+        };
+        let arg_list = vec![syn::parse_quote!(module = "ImplUser"), syn::parse_quote!(check_params)];
+        let result = expand_api_impl(make_api_arg(arg_list), impl_item, save_nothing).to_string();
+        // 警告放在方法体的最前面
+        let method_start = result.find("fn get_user").unwrap();
+        let warning_start = result.find("api_doc_warning_0").unwrap();
+        assert!(warning_start > method_start && warning_start < result.find("fn helper").unwrap());
+        assert!(result.contains("argument 'name' has no documented param"));
+        assert!(!result.contains("compile_error"));
+
+        // impl块上只能使用 module、base_path 以及 check_params
+        let impl_item: syn::ItemImpl = syn::parse_quote!(impl UserService {});
+        let result = expand_api_impl(make_api_arg(vec![syn::parse_quote!(name = "GetUser")]), impl_item, save_nothing);
+        assert!(result.to_string().contains("compile_error"));
+    }
+
+    #[test]
+    pub fn test_expand_api_trait_method() {
+        let method_item: syn::TraitItemMethod = syn::parse_quote! {
+            /// module TraitUser
+            /// fn TraitGetUser /v1/TraitGetUser post
+            fn get_user(&self, id: u64) {}
+        };
+        let result = expand_api_trait_method(make_api_arg(vec![syn::parse_quote!(check_params)]), true, method_item, save_nothing);
+        let result = result.to_string();
+        assert!(result.contains("api_doc_warning_0"));
+        assert!(!result.contains("compile_error"));
+
+        // 没有默认实现时警告做为错误报告
+        let method_item: syn::TraitItemMethod = syn::parse_quote! {
+            /// module TraitUser
+            /// fn TraitGetUserList /v1/TraitGetUserList post
+            fn get_user_list(&self, page: u32);
+        };
+        let result = expand_api_trait_method(make_api_arg(vec![syn::parse_quote!(check_params)]), true, method_item, save_nothing);
+        let result = result.to_string();
+        assert!(!result.contains("api_doc_warning_0"));
+        assert!(result.contains("compile_error"));
+        assert!(result.contains("argument 'page' has no documented param"));
+    }
+
+    #[test]
+    pub fn test_file_to_module_path() {
+        let root = Path::new("/project/src/lib.rs");
+        assert_eq!(file_to_module_path(root, root), "");
+        assert_eq!(file_to_module_path(root, Path::new("/project/src/model.rs")), "model");
+        assert_eq!(file_to_module_path(root, Path::new("/project/src/model/mod.rs")), "model");
+        assert_eq!(
+            file_to_module_path(root, Path::new("/project/src/model/user.rs")),
+            "model::user"
+        );
+        assert_eq!(file_to_module_path(root, Path::new("/other/src/model.rs")), "");
+    }
+
+    #[test]
+    pub fn test_serde_rename() {
+        let item: syn::ItemStruct = syn::parse_quote! {
+            #[derive(Serialize)]
+            #[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+            struct UserInfo {
+                #[serde(rename(serialize = "ID", deserialize = "id"))]
+                user_id: u64,
+                #[serde(skip_serializing)]
+                password: String,
+            }
+        };
+        assert_eq!(get_serde_rename_all(&item.attrs).as_deref(), Some("camelCase"));
+        let field_list: Vec<&syn::Field> = item.fields.iter().collect();
+        assert_eq!(get_serde_field_info(&field_list[0].attrs).rename.as_deref(), Some("ID"));
+        assert!(get_serde_field_info(&field_list[1].attrs).skip);
+
+        assert_eq!(apply_rename_all("camelCase", "user_home_dir"), "userHomeDir");
+        assert_eq!(apply_rename_all("PascalCase", "user_id"), "UserId");
+        assert_eq!(apply_rename_all("SCREAMING_SNAKE_CASE", "user_id"), "USER_ID");
+        assert_eq!(apply_rename_all("kebab-case", "user_id"), "user-id");
+        assert_eq!(apply_rename_all("SCREAMING-KEBAB-CASE", "user_id"), "USER-ID");
+        assert_eq!(apply_rename_all("lowercase", "user_id"), "user_id");
+    }
+}
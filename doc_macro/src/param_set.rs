@@ -0,0 +1,319 @@
+use crate::{get_doc_list, get_item_attrs, is_macro_attr};
+use doc_def::doc_params::{parse_param_set, ParamSetDocument};
+use once_cell::sync::Lazy;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use syn::spanned::Spanned;
+use syn::{Lit, Meta, NestedMeta};
+
+// 已展开的 #[doc_params] 定义的参数集合。源码中找不到的参数集合，如宏生成的，从这里查找
+static EXPANDED_PARAM_SET: Lazy<Mutex<HashMap<String, ParamSetDocument>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// 源码文件的查找结果。文件内容不变时使用缓存，以便在 rust-analyzer 等长期运行的进程中得到修改后的参数集合。
+// 修改时间的精度可能只有1秒，所以按文件长度以及内容的hash判断是否修改
+static FILE_SCAN_CACHE: Lazy<Mutex<HashMap<PathBuf, FileScan>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 一个源码文件的查找结果
+#[derive(Clone)]
+struct FileScan {
+    /// 文件长度以及内容的hash
+    content_key: (usize, u64),
+    /// 文件是否是 mod.rs 形式，决定子模块文件所在的目录
+    is_mod_rs: bool,
+    param_set_list: Vec<ParamSetDocument>,
+    /// 文件中声明的子模块文件，以及子模块文件是否是 mod.rs 形式
+    mod_file_list: Vec<(PathBuf, bool)>,
+}
+
+/// 解析参数集合的属性参数，如 #[doc_params(name = "auth")]，返回参数集合名
+pub(crate) fn parse_param_set_arg(arg_list: &[NestedMeta], span: proc_macro2::Span) -> Result<String, syn::Error> {
+    let mut result = String::new();
+    for arg_item in arg_list {
+        match arg_item {
+            NestedMeta::Meta(Meta::NameValue(val)) if val.path.is_ident("name") && result.is_empty() => {
+                result = match &val.lit {
+                    Lit::Str(lit) => lit.value().trim().to_string(),
+                    _ => return Err(syn::Error::new(val.lit.span(), "expected string literal")),
+                };
+                if result.is_empty() {
+                    return Err(syn::Error::new(val.lit.span(), "name can not be empty"));
+                }
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    arg_item.span(),
+                    "unknown param set argument, expected name = \"...\"",
+                ));
+            }
+        }
+    }
+    if result.is_empty() {
+        return Err(syn::Error::new(span, "doc_params need name = \"...\""));
+    }
+
+    Ok(result)
+}
+
+/// 记录已展开的参数集合。同名的参数集合已展开时返回false
+pub(crate) fn register_param_set(param_set: ParamSetDocument) -> bool {
+    let mut expanded_map = EXPANDED_PARAM_SET.lock().unwrap();
+    if expanded_map.contains_key(&param_set.name) {
+        return false;
+    }
+    expanded_map.insert(param_set.name.clone(), param_set);
+    true
+}
+
+/// 获取当前编译目标中所有的参数集合，以及查找源码时出现的问题。
+/// 宏的展开顺序不确定，所以从编译目标的根文件开始沿模块树查找 #[doc_params]；
+/// 源码中找不到的参数集合，如 #[cfg] 下或者宏生成的，只有在已展开时才能找到
+pub(crate) fn get_param_set_list() -> (Vec<ParamSetDocument>, Vec<String>) {
+    let (mut result, problem_list) = match std::env::var("CARGO_MANIFEST_DIR") {
+        Ok(dir) => match get_target_root(Path::new(&dir)) {
+            Some(root) => find_param_set_list(&root),
+            None => (
+                Vec::new(),
+                vec!["can not find the root file of the current target, only expanded #[doc_params] are used".to_string()],
+            ),
+        },
+        Err(_) => (Vec::new(), Vec::new()),
+    };
+
+    let expanded_map = EXPANDED_PARAM_SET.lock().unwrap();
+    let mut expanded_list: Vec<&ParamSetDocument> = expanded_map
+        .values()
+        .filter(|val| !result.iter().any(|item| item.name == val.name))
+        .collect();
+    expanded_list.sort_by(|a, b| a.name.cmp(&b.name));
+    result.extend(expanded_list.into_iter().cloned());
+
+    (result, problem_list)
+}
+
+/// 获取当前编译目标的根文件。cargo 编译时会设置 CARGO_BIN_NAME、CARGO_CRATE_NAME 等环境变量
+pub(crate) fn get_target_root(manifest_dir: &Path) -> Option<PathBuf> {
+    let package_name = std::env::var("CARGO_PKG_NAME").unwrap_or_default();
+    let crate_name = std::env::var("CARGO_CRATE_NAME").unwrap_or_default();
+    let src_dir = manifest_dir.join("src");
+
+    let mut candidate_list = Vec::new();
+    match std::env::var("CARGO_BIN_NAME") {
+        Ok(bin_name) => {
+            if bin_name == package_name {
+                candidate_list.push(src_dir.join("main.rs"));
+            }
+            candidate_list.push(src_dir.join("bin").join(format!("{}.rs", &bin_name)));
+            candidate_list.push(src_dir.join("bin").join(&bin_name).join("main.rs"));
+        }
+        Err(_) if crate_name.is_empty() || crate_name == package_name.replace('-', "_") => {
+            candidate_list.push(src_dir.join("lib.rs"));
+        }
+        Err(_) => {
+            // 示例、集成测试以及性能测试
+            for dir_name in ["examples", "tests", "benches"] {
+                for file_name in [crate_name.clone(), crate_name.replace('_', "-")] {
+                    let dir = manifest_dir.join(dir_name);
+                    candidate_list.push(dir.join(format!("{}.rs", &file_name)));
+                    candidate_list.push(dir.join(&file_name).join("main.rs"));
+                }
+            }
+        }
+    }
+
+    candidate_list.into_iter().find(|val| val.is_file())
+}
+
+/// 从根文件开始沿模块树查找参数集合。同名的参数集合只保留第一个，重复定义由 #[doc_params] 报告
+fn find_param_set_list(root: &Path) -> (Vec<ParamSetDocument>, Vec<String>) {
+    let mut result: Vec<ParamSetDocument> = Vec::new();
+    let mut problem_list = Vec::new();
+    let mut file_list = vec![(root.to_path_buf(), true)];
+    let mut index = 0;
+    while index < file_list.len() {
+        let (path, is_mod_rs) = file_list[index].clone();
+        index += 1;
+        match scan_file(&path, is_mod_rs) {
+            Ok(file_scan) => {
+                for param_set in file_scan.param_set_list {
+                    if !result.iter().any(|val| val.name == param_set.name) {
+                        result.push(param_set);
+                    }
+                }
+                for mod_file in file_scan.mod_file_list {
+                    if !file_list.contains(&mod_file) {
+                        file_list.push(mod_file);
+                    }
+                }
+            }
+            Err(err) => problem_list.push(err),
+        }
+    }
+
+    (result, problem_list)
+}
+
+/// 查找一个源码文件中的参数集合以及子模块文件，文件内容未修改时使用缓存，不再解析
+fn scan_file(path: &Path, is_mod_rs: bool) -> Result<FileScan, String> {
+    let content = std::fs::read_to_string(path).map_err(|err| format!("can not read {}: {}", path.display(), err))?;
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    let content_key = (content.len(), hasher.finish());
+    let mut cache_map = FILE_SCAN_CACHE.lock().unwrap();
+    if let Some(val) = cache_map.get(path) {
+        if val.content_key == content_key && val.is_mod_rs == is_mod_rs {
+            return Ok(val.clone());
+        }
+    }
+
+    let file = syn::parse_file(&content).map_err(|err| format!("can not parse {}: {}", path.display(), err))?;
+    let file_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    // 非 mod.rs 形式的文件，如 user.rs，其子模块在 user 目录中
+    let mod_dir = match (is_mod_rs, path.file_stem()) {
+        (false, Some(stem)) => file_dir.join(stem),
+        _ => file_dir.to_path_buf(),
+    };
+
+    let mut file_scan = FileScan {
+        content_key,
+        is_mod_rs,
+        param_set_list: Vec::new(),
+        mod_file_list: Vec::new(),
+    };
+    collect_item_list(&file.items, file_dir, &mod_dir, &mut file_scan);
+    cache_map.insert(path.to_path_buf(), file_scan.clone());
+
+    Ok(file_scan)
+}
+
+/// 查找 #[doc_params] 标记的项以及子模块。path_dir 为 #[path] 的相对目录，mod_dir 为子模块文件所在的目录。
+/// 无法判断 #[cfg] 是否启用，所以跳过带 #[cfg] 的项；参数有误的参数集合由 #[doc_params] 报告错误，这里也跳过
+fn collect_item_list(item_list: &[syn::Item], path_dir: &Path, mod_dir: &Path, file_scan: &mut FileScan) {
+    for item in item_list {
+        let attrs = match get_item_attrs(item) {
+            Some(val) => val,
+            None => continue,
+        };
+        if attrs.iter().any(|val| val.path.is_ident("cfg")) {
+            continue;
+        }
+
+        if let syn::Item::Mod(mod_item) = item {
+            let mod_name = mod_item.ident.to_string();
+            let mod_name = mod_name.trim_start_matches("r#");
+            let path_attr = get_path_attr(attrs);
+            match (&mod_item.content, path_attr) {
+                (Some((_, content)), path_attr) => {
+                    let child_dir = mod_dir.join(path_attr.as_deref().unwrap_or(mod_name));
+                    collect_item_list(content, &child_dir, &child_dir, file_scan);
+                }
+                (None, Some(path_attr)) => file_scan.mod_file_list.push((path_dir.join(path_attr), true)),
+                (None, None) => {
+                    let file_path = mod_dir.join(format!("{}.rs", mod_name));
+                    if file_path.is_file() {
+                        file_scan.mod_file_list.push((file_path, false));
+                    } else {
+                        file_scan.mod_file_list.push((mod_dir.join(mod_name).join("mod.rs"), true));
+                    }
+                }
+            }
+        }
+
+        for attr_item in attrs.iter().filter(|val| is_macro_attr(val, "doc_params")) {
+            let arg_list: Vec<NestedMeta> = match attr_item.parse_meta() {
+                Ok(Meta::List(val)) => val.nested.into_iter().collect(),
+                _ => continue,
+            };
+            let name = match parse_param_set_arg(&arg_list, attr_item.span()) {
+                Ok(val) => val,
+                Err(_) => continue,
+            };
+            let parse_result = parse_param_set(&get_doc_list(attrs));
+            if !parse_result.error_list.is_empty() {
+                continue;
+            }
+            file_scan.param_set_list.push(ParamSetDocument {
+                name,
+                param_list: parse_result.param_list,
+            });
+        }
+    }
+}
+
+/// 获取 #[path = "..."] 指定的路径
+fn get_path_attr(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr_item| match attr_item.parse_meta() {
+        Ok(Meta::NameValue(syn::MetaNameValue {
+            path,
+            lit: Lit::Str(lit),
+            ..
+        })) if path.is_ident("path") => Some(lit.value()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::param_set::find_param_set_list;
+
+    #[test]
+    pub fn test_find_param_set_list() {
+        let dir = std::env::temp_dir().join(format!("doc_macro_param_set_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("user")).unwrap();
+        std::fs::create_dir_all(dir.join("other")).unwrap();
+        let write = |name: &str, content: &str| std::fs::write(dir.join(name), content).unwrap();
+        write(
+            "lib.rs",
+            "mod user;
+            #[path = \"other/renamed.rs\"]
+            mod admin;
+            #[cfg(test)]
+            mod test;
+            mod inline {
+                mod page;
+            }
+            /// Token string required 登录令牌
+            #[doc_params(name = \"auth\")]
+            pub struct Auth;",
+        );
+        write(
+            "user.rs",
+            "mod detail;
+            /// Id int
+            #[rust_document_generator::doc_params(name = \"user\")]
+            pub struct User;
+            /// Id int
+            #[cfg(feature = \"admin\")]
+            #[doc_params(name = \"conditional\")]
+            pub struct Conditional;",
+        );
+        write("user/detail.rs", "/// Detail string\n#[doc_params(name = \"detail\")]\npub struct Detail;");
+        write("other/renamed.rs", "/// Name string\n#[doc_params(name = \"admin\")]\npub struct Admin;");
+        write("test.rs", "/// Name string\n#[doc_params(name = \"test\")]\npub struct Test;");
+        std::fs::create_dir_all(dir.join("inline")).unwrap();
+        write("inline/page.rs", "/// Page int\n#[doc_params(name = \"page\")]\npub struct Page;");
+        // 不在模块树中的文件
+        write("unused.rs", "/// Name string\n#[doc_params(name = \"unused\")]\npub struct Unused;");
+
+        let (param_set_list, problem_list) = find_param_set_list(&dir.join("lib.rs"));
+        assert!(problem_list.is_empty(), "{:?}", problem_list);
+        let mut name_list: Vec<&str> = param_set_list.iter().map(|val| val.name.as_str()).collect();
+        name_list.sort_unstable();
+        assert_eq!(name_list, vec!["admin", "auth", "detail", "page", "user"]);
+        assert_eq!(param_set_list[0].param_list[0].name, "Token");
+
+        // 文件修改后重新查找，无法解析的文件做为问题返回。修改时间可能不变，内容改变即可
+        write("user.rs", "/// Id int\n#[doc_params(name = \"renamed\")]\npub struct User;");
+        write("other/renamed.rs", "pub struct {");
+        let (param_set_list, problem_list) = find_param_set_list(&dir.join("lib.rs"));
+        let mut name_list: Vec<&str> = param_set_list.iter().map(|val| val.name.as_str()).collect();
+        name_list.sort_unstable();
+        assert_eq!(name_list, vec!["auth", "page", "renamed"]);
+        assert_eq!(problem_list.len(), 1);
+        assert!(problem_list[0].contains("renamed.rs"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
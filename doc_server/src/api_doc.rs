@@ -1,9 +1,14 @@
+use crate::api_view::{find_replacement_anchor, ApiItem, TypeStore};
 use crate::project;
+use doc_def::doc_envelope::EnvelopeDocument;
+use doc_def::doc_module::ModuleDocument;
+use doc_def::doc_type::TypeDocument;
 use doc_def::document::ApiDocument;
 use doc_def::file::ItemType;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::str::FromStr;
 
 #[derive(Serialize, Deserialize)]
 pub struct Document {
@@ -11,27 +16,72 @@ pub struct Document {
     pub header: String,
     #[serde(rename = "ApiList")]
     pub api_list: Vec<ApiDocument>,
+    #[serde(rename = "TypeList")]
+    pub type_list: Vec<TypeDocument>,
+    #[serde(rename = "ModuleList")]
+    pub module_list: Vec<ModuleDocument>,
+    #[serde(rename = "EnvelopeList", default)]
+    pub envelope_list: Vec<EnvelopeDocument>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 pub struct GroupedDocument{
     #[serde(rename = "Header")]
     pub header: String,
     #[serde(rename = "GroupApiList")]
     pub group_api: Vec<GroupApi>,
+    /// 当前展示的描述语言。为空时展示默认描述
+    #[serde(rename = "Lang")]
+    pub lang: String,
+    /// 文档中出现的所有描述语言
+    #[serde(rename = "LangList")]
+    pub lang_list: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 pub struct GroupApi{
-    #[serde(rename = "ModuleName")]
-    pub module_name:String,
+    /// 分组名。按模块分组时为模块名，按标签分组时为标签名
+    #[serde(rename = "GroupName")]
+    pub group_name:String,
+    /// 分组标题。按模块分组且模块指定了标题时不为空
+    #[serde(rename = "Title")]
+    pub title:String,
+    /// 分组描述。按模块分组时为模块描述
+    #[serde(rename = "Desc")]
+    pub desc:String,
     #[serde(rename = "ApiList")]
-    pub api_list:Vec<ApiDocument>
+    pub api_list:Vec<ApiItem>
+}
+
+/// API的分组方式
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum GroupType {
+    /// 按模块分组，每个API只属于一个分组
+    #[serde(rename = "module")]
+    Module,
+    /// 按标签分组，一个API可以出现在多个分组中
+    #[serde(rename = "tag")]
+    Tag,
 }
 
+impl FromStr for GroupType {
+    type Err = String;
+
+    fn from_str(val: &str) -> Result<GroupType, String> {
+        match val.to_lowercase().as_str() {
+            "" | "module" => Ok(GroupType::Module),
+            "tag" => Ok(GroupType::Tag),
+            _ => Err(format!("unknown group type:{}", val)),
+        }
+    }
+}
+
+/// 没有标签的API所在的分组名
+const UNTAGGED_GROUP_NAME: &str = "未分类";
+
 pub fn get_api_list(project_item: &project::ProjectInfo) -> Result<Document, String> {
     let result = get_api_list_detail(doc_def::file::get_doc_file_path(&project_item.path));
-    return match result {
+    match result {
         Ok(result) => {
             let _a = save_to_local(project_item.path.clone(), project_item.id.clone());
             Ok(result)
@@ -41,54 +91,206 @@ pub fn get_api_list(project_item: &project::ProjectInfo) -> Result<Document, Str
             let cache_path = cache_path.join(project_item.id.clone());
             match get_api_list_detail(cache_path.to_str().unwrap().to_string()) {
                 Ok(val) => Ok(val),
-                Err(_) => Err(format!("load {} err:{}",&project_item.name,err.to_string())),
+                Err(_) => Err(format!("load {} err:{}",&project_item.name,err)),
             }
         }
-    };
+    }
 }
 
-pub fn get_grouped_api_list(project_item: &project::ProjectInfo) -> Result<GroupedDocument, String>{
+/// 获取分组后的API列表。lang_candidate_list 为按优先级排列的期望语言，使用其中第一个文档支持的语言
+pub fn get_grouped_api_list(
+    project_item: &project::ProjectInfo,
+    group_type: GroupType,
+    lang_candidate_list: &[String],
+) -> Result<GroupedDocument, String>{
     let doc_obj= get_api_list(project_item)?;
+    Ok(group_api_list(doc_obj,group_type,lang_candidate_list))
+}
+
+/// 按分组方式对文档中的API分组
+fn group_api_list(doc_obj: Document, group_type: GroupType, lang_candidate_list: &[String]) -> GroupedDocument {
+    let type_store=TypeStore::new(&doc_obj.type_list);
+
+    let mut lang_list:Vec<String>=Vec::new();
+    for item in &doc_obj.api_list{
+        for lang in item.get_lang_list(){
+            if !lang_list.contains(&lang){
+                lang_list.push(lang);
+            }
+        }
+    }
+    lang_list.sort();
+    let lang=select_lang(lang_candidate_list,&lang_list);
+
     let mut grouped_api:Vec<GroupApi>=Vec::new();
     for item in &doc_obj.api_list{
+        let mut doc=item.clone();
+        if !lang.is_empty(){
+            doc.localize(&lang);
+        }
+        // 响应与信封组合后展示，信封不存在时在页面中提示
+        let envelope_result=doc.apply_envelope(&doc_obj.envelope_list);
+        let mut item=type_store.build_api_item(&doc);
+        if let Err(err)=envelope_result{
+            item.warning_list.push(err);
+        }
+        item.replacement_anchor=find_replacement_anchor(&item.doc.replacement,&doc_obj.api_list);
 
-        let mut is_found=false;
-        for group_item in &mut grouped_api{
-            if &group_item.module_name==&item.module_name{
-                group_item.api_list.push(item.clone());
-                is_found=true;
+        let group_name_list=match group_type{
+            GroupType::Module=>vec![item.doc.module_name.clone()],
+            GroupType::Tag=>{
+                if item.doc.tag_list.is_empty(){
+                    vec![UNTAGGED_GROUP_NAME.to_string()]
+                }else{
+                    item.doc.tag_list.clone()
+                }
             }
+        };
+
+        for group_name in group_name_list{
+            add_to_group(&mut grouped_api,group_name,item.clone());
         }
+    }
 
-        if is_found==false{
-            grouped_api.push( GroupApi{
-                module_name:item.module_name.clone(),
-                api_list:vec![item.clone()],
-            });
+    // 按模块分组时使用模块文档中的标题、描述以及展示顺序
+    if group_type==GroupType::Module{
+        for group_item in &mut grouped_api{
+            if let Some(module_item)=doc_obj.module_list.iter().find(|val| val.name==group_item.group_name){
+                group_item.title=module_item.title.clone();
+                group_item.desc=module_item.desc.clone();
+            }
         }
+        let get_order=|group_item:&GroupApi|{
+            doc_obj.module_list
+                .iter()
+                .find(|val| val.name==group_item.group_name)
+                .and_then(|val| val.order)
+                .unwrap_or(i32::MAX)
+        };
+        grouped_api.sort_by_key(|val| get_order(val));
     }
 
-    Ok(GroupedDocument{
+    GroupedDocument{
         header:doc_obj.header,
         group_api:grouped_api,
-    })
+        lang,
+        lang_list,
+    }
+}
+
+/// 从期望语言中选择文档支持的语言。期望语言可以只匹配主语言，如 en-US 匹配 en。
+/// 期望语言为空字符串时表示使用默认描述。都不支持时返回空字符串
+pub fn select_lang(lang_candidate_list: &[String], lang_list: &[String]) -> String {
+    for candidate in lang_candidate_list {
+        let candidate = candidate.trim().to_lowercase();
+        if candidate.is_empty() {
+            return String::new();
+        }
+        if lang_list.contains(&candidate) {
+            return candidate;
+        }
+
+        let primary = candidate.split(['-', '_']).next().unwrap_or_default();
+        if let Some(val) = lang_list
+            .iter()
+            .find(|val| val.split(['-', '_']).next() == Some(primary))
+        {
+            return val.clone();
+        }
+    }
+
+    String::new()
+}
+
+/// 解析 Accept-Language 请求头，按权重从高到低返回语言列表，忽略通配符以及权重为0的语言
+pub fn parse_accept_language(accept_language: &str) -> Vec<String> {
+    let mut lang_list: Vec<(String, f32)> = Vec::new();
+    for item in accept_language.split(',') {
+        let mut part_iter = item.split(';');
+        let lang = part_iter.next().unwrap_or_default().trim();
+        if lang.is_empty() || lang == "*" {
+            continue;
+        }
+
+        let mut weight = 1.0f32;
+        for part in part_iter {
+            if let Some(val) = part.trim().strip_prefix("q=") {
+                weight = val.trim().parse().unwrap_or(0.0);
+            }
+        }
+        if weight > 0.0 {
+            lang_list.push((lang.to_string(), weight));
+        }
+    }
+
+    // 稳定排序，权重相同时保留原顺序
+    lang_list.sort_by(|a, b| b.1.total_cmp(&a.1));
+    lang_list.into_iter().map(|val| val.0).collect()
+}
+
+fn add_to_group(grouped_api:&mut Vec<GroupApi>,group_name:String,item:ApiItem){
+    for group_item in grouped_api.iter_mut(){
+        if group_item.group_name==group_name{
+            group_item.api_list.push(item);
+            return;
+        }
+    }
+
+    grouped_api.push( GroupApi{
+        group_name,
+        title:String::new(),
+        desc:String::new(),
+        api_list:vec![item],
+    });
 }
 
 fn get_api_list_detail(file_path: String) -> Result<Document, String> {
     let content_list = doc_def::file::get_val_list(&file_path)?;
 
     let mut api_list = Vec::new();
+    let mut type_list = Vec::new();
+    let mut module_list = Vec::new();
+    let mut envelope_list = Vec::new();
     let mut header = String::new();
     for item in content_list {
         if item.item_type == (ItemType::Header as u32) {
             header = item.content.clone();
         } else if item.item_type == (ItemType::Api as u32) {
             match serde_json::from_str::<ApiDocument>(&item.content) {
-                Ok(val) => {
+                Ok(mut val) => {
+                    // 旧版本的doc.data中只有一个返回值
+                    val.migrate_legacy_return();
                     api_list.push(val);
                 }
                 Err(err) => {
-                    return Err(format!("deserialize error:{}", err.to_string()));
+                    return Err(format!("deserialize error:{}", err));
+                }
+            }
+        } else if item.item_type == (ItemType::Type as u32) {
+            match serde_json::from_str::<TypeDocument>(&item.content) {
+                Ok(val) => {
+                    type_list.push(val);
+                }
+                Err(err) => {
+                    return Err(format!("deserialize error:{}", err));
+                }
+            }
+        } else if item.item_type == (ItemType::Module as u32) {
+            match serde_json::from_str::<ModuleDocument>(&item.content) {
+                Ok(val) => {
+                    module_list.push(val);
+                }
+                Err(err) => {
+                    return Err(format!("deserialize error:{}", err));
+                }
+            }
+        } else if item.item_type == (ItemType::Envelope as u32) {
+            match serde_json::from_str::<EnvelopeDocument>(&item.content) {
+                Ok(val) => {
+                    envelope_list.push(val);
+                }
+                Err(err) => {
+                    return Err(format!("deserialize error:{}", err));
                 }
             }
         } else {
@@ -96,13 +298,19 @@ fn get_api_list_detail(file_path: String) -> Result<Document, String> {
         }
     }
 
-    return Ok(Document { header, api_list });
+    Ok(Document {
+        header,
+        api_list,
+        type_list,
+        module_list,
+        envelope_list,
+    })
 }
 
 const SAVE_PATH: &str = "./DocCache";
 fn save_to_local(file_path: String, project_id: String) -> Result<(), String> {
     let cache_path = Path::new(SAVE_PATH);
-    if cache_path.exists() == false {
+    if !cache_path.exists() {
         let _ = fs::create_dir_all(SAVE_PATH);
     }
 
@@ -117,8 +325,118 @@ fn save_to_local(file_path: String, project_id: String) -> Result<(), String> {
     }
 
     let source = Path::new(&file_path);
-    return match std::fs::copy(source.join(doc_def::file::FILE_NAME), doc_path.as_path()) {
+    match std::fs::copy(source.join(doc_def::file::FILE_NAME), doc_path.as_path()) {
         Ok(_) => Ok(()),
         Err(err) => Err(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api_doc::{
+        group_api_list, parse_accept_language, select_lang, Document, GroupType, UNTAGGED_GROUP_NAME,
     };
+    use doc_def::doc_module::ModuleDocument;
+    use doc_def::document::ApiDocument;
+
+    fn make_api(module_name: &str, name: &str, tag_list: &[&str]) -> ApiDocument {
+        let mut api_item = ApiDocument::default();
+        api_item.module_name = module_name.to_string();
+        api_item.name = name.to_string();
+        api_item.tag_list = tag_list.iter().map(|val| val.to_string()).collect();
+        api_item
+    }
+
+    fn make_document() -> Document {
+        let mut api_item = make_api("User", "GetUser", &["Admin", "Public"]);
+        api_item.desc = "获取用户".to_string();
+        api_item.desc_map.insert("en".to_string(), "Get user".to_string());
+        Document {
+            header: "".to_string(),
+            api_list: vec![
+                api_item,
+                make_api("Lobby", "Enter", &[]),
+                make_api("Shop", "Buy", &["Public"]),
+            ],
+            type_list: Vec::new(),
+            module_list: vec![
+                ModuleDocument {
+                    name: "Shop".to_string(),
+                    title: "商店".to_string(),
+                    order: Some(1),
+                    ..ModuleDocument::default()
+                },
+                ModuleDocument {
+                    name: "Lobby".to_string(),
+                    order: Some(2),
+                    ..ModuleDocument::default()
+                },
+            ],
+            envelope_list: Vec::new(),
+        }
+    }
+
+    #[test]
+    pub fn test_group_by_module() {
+        let grouped = group_api_list(make_document(), GroupType::Module, &[]);
+        let name_list: Vec<&str> = grouped.group_api.iter().map(|val| val.group_name.as_str()).collect();
+        // 按模块的展示顺序排列，未指定顺序的排在最后
+        assert_eq!(name_list, vec!["Shop", "Lobby", "User"]);
+        assert_eq!(grouped.group_api[0].title, "商店");
+        assert_eq!(grouped.lang, "");
+        assert_eq!(grouped.lang_list, vec!["en"]);
+        assert_eq!(grouped.group_api[2].api_list[0].doc.desc, "获取用户");
+    }
+
+    #[test]
+    pub fn test_group_by_tag() {
+        let grouped = group_api_list(make_document(), GroupType::Tag, &["en-US".to_string()]);
+        let name_list: Vec<&str> = grouped.group_api.iter().map(|val| val.group_name.as_str()).collect();
+        assert_eq!(name_list, vec!["Admin", "Public", UNTAGGED_GROUP_NAME]);
+        // 有多个标签的API出现在每个分组中
+        assert_eq!(grouped.group_api[1].api_list.len(), 2);
+        assert_eq!(grouped.group_api[1].api_list[0].doc.name, "GetUser");
+        assert_eq!(grouped.group_api[2].api_list[0].doc.name, "Enter");
+        assert_eq!(grouped.lang, "en");
+        assert_eq!(grouped.group_api[0].api_list[0].doc.desc, "Get user");
+    }
+
+    #[test]
+    pub fn test_unknown_envelope() {
+        let mut document = make_document();
+        document.api_list[1].envelope = "Reslut".to_string();
+        let grouped = group_api_list(document, GroupType::Module, &[]);
+        let warning_list = &grouped.group_api[1].api_list[0].warning_list;
+        assert_eq!(warning_list.len(), 1);
+        assert!(warning_list[0].contains("'Reslut'"));
+        assert!(grouped.group_api[0].api_list[0].warning_list.is_empty());
+    }
+
+    #[test]
+    pub fn test_select_lang() {
+        let lang_list = vec!["en".to_string(), "zh-cn".to_string()];
+        let select = |candidate_list: &[&str]| {
+            let candidate_list: Vec<String> = candidate_list.iter().map(|val| val.to_string()).collect();
+            select_lang(&candidate_list, &lang_list)
+        };
+        assert_eq!(select(&["en-US"]), "en");
+        assert_eq!(select(&["ZH-CN", "en"]), "zh-cn");
+        assert_eq!(select(&["zh_TW"]), "zh-cn");
+        assert_eq!(select(&["fr", "en"]), "en");
+        // 空字符串表示使用默认描述
+        assert_eq!(select(&["", "en"]), "");
+        assert_eq!(select(&["fr"]), "");
+        assert_eq!(select(&[]), "");
+    }
+
+    #[test]
+    pub fn test_parse_accept_language() {
+        assert_eq!(
+            parse_accept_language("zh-CN,zh;q=0.9,en-US;q=0.8,en;q=0.9"),
+            vec!["zh-CN", "zh", "en", "en-US"]
+        );
+        assert_eq!(parse_accept_language("fr;q=0, *;q=0.5, en"), vec!["en"]);
+        assert_eq!(parse_accept_language("de;q=abc, *"), Vec::<String>::new());
+        assert_eq!(parse_accept_language(""), Vec::<String>::new());
+    }
 }
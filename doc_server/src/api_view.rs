@@ -0,0 +1,376 @@
+use doc_def::doc_type::{parse_type_ref, TypeDocument};
+use doc_def::document::{
+    ApiDocument, ApiParam, ApiParamConstraints, ApiResponse, ParamLocation, RequestBodyKind,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 用于页面展示的API信息
+#[derive(Serialize, Clone)]
+pub struct ApiItem {
+    #[serde(flatten)]
+    pub doc: ApiDocument,
+    /// 页面中的锚点
+    #[serde(rename = "Anchor")]
+    pub anchor: String,
+    /// 替代API的锚点。找不到替代API时为空
+    #[serde(rename = "ReplacementAnchor")]
+    pub replacement_anchor: String,
+    /// 展开类型后的参数列表
+    #[serde(rename = "ParamViewList")]
+    pub param_view_list: Vec<FieldView>,
+    /// 响应列表，每个响应单独展示
+    #[serde(rename = "ResponseViewList")]
+    pub response_view_list: Vec<ResponseView>,
+    /// 请求体的字段列表。请求体为类型时为类型展开后的字段
+    #[serde(rename = "RequestBodyFieldList")]
+    pub request_body_field_list: Vec<FieldView>,
+    /// 展示时发现的问题，如找不到API使用的信封
+    #[serde(rename = "WarningList")]
+    pub warning_list: Vec<String>,
+}
+
+/// 用于页面展示的响应信息
+#[derive(Serialize, Clone)]
+pub struct ResponseView {
+    #[serde(flatten)]
+    pub response: ApiResponse,
+    /// 响应的字段列表。文档中写出的字段优先，否则为返回值类型展开后的字段
+    #[serde(rename = "FieldViewList")]
+    pub field_view_list: Vec<FieldView>,
+}
+
+/// 用于页面展示的字段信息。参数与返回值的字段都使用此结构展示
+#[derive(Serialize, Clone)]
+pub struct FieldView {
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// 从根开始的完整路径，如 user.address.city
+    #[serde(rename = "Path")]
+    pub path: String,
+    #[serde(rename = "FieldType")]
+    pub field_type: String,
+    #[serde(rename = "Required")]
+    pub required: bool,
+    #[serde(rename = "Desc")]
+    pub desc: String,
+    /// 参数的传递位置。返回值字段为None
+    #[serde(rename = "Location")]
+    pub location: Option<ParamLocation>,
+    /// 参数的取值约束。返回值字段没有约束
+    #[serde(rename = "Constraints")]
+    pub constraints: ApiParamConstraints,
+    #[serde(rename = "Children")]
+    pub children: Vec<FieldView>,
+}
+
+/// 文档中定义的所有类型
+pub struct TypeStore {
+    /// 以带模块路径的类型名为key
+    type_map: HashMap<String, TypeDocument>,
+    /// 不带路径的类型名对应的带路径类型名。不同模块中有同名类型时使用第一个
+    name_map: HashMap<String, String>,
+}
+
+impl TypeStore {
+    pub fn new(type_list: &[TypeDocument]) -> TypeStore {
+        let mut type_map = HashMap::new();
+        let mut name_map = HashMap::new();
+        for item in type_list {
+            let full_name = item.full_name();
+            name_map.entry(item.name.clone()).or_insert_with(|| full_name.clone());
+            type_map.insert(full_name, item.clone());
+        }
+
+        TypeStore { type_map, name_map }
+    }
+
+    /// 查找类型。类型名带路径时优先按路径查找，如 crate::model::UserInfo ，找不到时按类型名查找
+    fn find_type(&self, type_name: &str) -> Option<&TypeDocument> {
+        let type_ref = parse_type_ref(type_name);
+        let path = match type_ref.path.as_str() {
+            "crate" => "",
+            val => val.strip_prefix("crate::").unwrap_or(val),
+        };
+        if !type_ref.path.is_empty() {
+            let full_name = if path.is_empty() {
+                type_ref.name.clone()
+            } else {
+                format!("{}::{}", path, &type_ref.name)
+            };
+            if let Some(val) = self.type_map.get(&full_name) {
+                return Some(val);
+            }
+        }
+
+        let full_name = self.name_map.get(&type_ref.name)?;
+        self.type_map.get(full_name)
+    }
+
+    /// 生成展示用的API信息
+    pub fn build_api_item(&self, doc: &ApiDocument) -> ApiItem {
+        let response_view_list = doc
+            .response_list
+            .iter()
+            .map(|response_item| {
+                let field_view_list = if response_item.field_list.is_empty() {
+                    self.build_field_list(&response_item.type_name, "")
+                        .unwrap_or_default()
+                } else {
+                    self.build_param_list(&response_item.field_list)
+                };
+                ResponseView {
+                    response: response_item.clone(),
+                    field_view_list,
+                }
+            })
+            .collect();
+
+        let request_body_field_list = match &doc.request_body {
+            Some(body) => match body.kind {
+                RequestBodyKind::Type => self.build_field_list(&body.type_name, "").unwrap_or_default(),
+                RequestBodyKind::Fields => self.build_param_list(&body.field_list),
+                RequestBodyKind::Example => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+
+        ApiItem {
+            doc: doc.clone(),
+            anchor: get_api_anchor(doc),
+            replacement_anchor: "".to_string(),
+            param_view_list: self.build_param_list(&doc.param_list),
+            response_view_list,
+            request_body_field_list,
+            warning_list: Vec::new(),
+        }
+    }
+
+    /// 展开参数列表。参数类型为已文档化的类型时，其字段会做为子参数
+    pub fn build_param_list(&self, param_list: &[ApiParam]) -> Vec<FieldView> {
+        self.build_param_list_detail(param_list, "")
+    }
+
+    /// 展开参数列表。文档中写出的子参数优先于类型的字段
+    fn build_param_list_detail(&self, param_list: &[ApiParam], path_prefix: &str) -> Vec<FieldView> {
+        let mut result = Vec::new();
+        for param_item in param_list {
+            if param_item.flatten {
+                // param type {TypeName} 形式，类型的字段直接做为参数
+                if let Some(mut field_list) = self.build_field_list(&param_item.param_type, "") {
+                    for field_item in field_list.iter_mut() {
+                        field_item.location = param_item.location;
+                    }
+                    result.append(&mut field_list);
+                    continue;
+                }
+            }
+
+            let name = if param_item.name.is_empty() {
+                param_item.param_type.clone()
+            } else {
+                param_item.name.clone()
+            };
+            let path = if path_prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", path_prefix, &name)
+            };
+            let children = if param_item.children.is_empty() {
+                self.build_field_list(&param_item.param_type, &path)
+                    .unwrap_or_default()
+            } else {
+                self.build_param_list_detail(&param_item.children, &path)
+            };
+            result.push(FieldView {
+                name,
+                path,
+                field_type: param_item.param_type.clone(),
+                required: param_item.required,
+                desc: param_item.desc.clone(),
+                location: param_item.location,
+                constraints: param_item.constraints.clone(),
+                children,
+            });
+        }
+
+        result
+    }
+
+    /// 展开类型的字段。类型未文档化时返回None
+    pub fn build_field_list(&self, type_name: &str, path_prefix: &str) -> Option<Vec<FieldView>> {
+        let mut visited_list = Vec::new();
+        self.build_field_list_detail(type_name, path_prefix, &mut visited_list)
+    }
+
+    fn build_field_list_detail(
+        &self,
+        type_name: &str,
+        path_prefix: &str,
+        visited_list: &mut Vec<String>,
+    ) -> Option<Vec<FieldView>> {
+        let type_doc = self.find_type(type_name)?;
+
+        // 类型自引用时不再继续展开
+        let full_name = type_doc.full_name();
+        if visited_list.contains(&full_name) {
+            return None;
+        }
+        visited_list.push(full_name);
+
+        let mut result = Vec::new();
+        for field_item in &type_doc.field_list {
+            let path = if path_prefix.is_empty() {
+                field_item.name.clone()
+            } else {
+                format!("{}.{}", path_prefix, &field_item.name)
+            };
+            let children = self
+                .build_field_list_detail(&field_item.field_type, &path, visited_list)
+                .unwrap_or_default();
+
+            result.push(FieldView {
+                name: field_item.name.clone(),
+                path,
+                field_type: field_item.field_type.clone(),
+                required: !field_item.optional,
+                desc: field_item.desc.clone(),
+                location: None,
+                constraints: ApiParamConstraints::default(),
+                children,
+            });
+        }
+
+        visited_list.pop();
+        Some(result)
+    }
+}
+
+/// 获取API在页面中的锚点
+pub fn get_api_anchor(doc: &ApiDocument) -> String {
+    let anchor = format!("api_{}_{}", &doc.module_name, &doc.name);
+    anchor
+        .chars()
+        .map(|val| if val.is_alphanumeric() { val } else { '_' })
+        .collect()
+}
+
+/// 查找替代API的锚点。替代API可以是 {ApiName} 或者 {ModuleName}.{ApiName}
+pub fn find_replacement_anchor(replacement: &str, api_list: &[ApiDocument]) -> String {
+    if replacement.is_empty() {
+        return "".to_string();
+    }
+
+    for item in api_list {
+        let full_name = format!("{}.{}", &item.module_name, &item.name);
+        if item.name == replacement || full_name == replacement {
+            return get_api_anchor(item);
+        }
+    }
+
+    "".to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api_view::TypeStore;
+    use doc_def::doc_type::{TypeDocument, TypeField};
+    use doc_def::document::{ApiParam, ParamLocation};
+
+    fn make_type(module_path: &str, name: &str, field_list: &[(&str, &str)]) -> TypeDocument {
+        TypeDocument {
+            name: name.to_string(),
+            module_path: module_path.to_string(),
+            desc: "".to_string(),
+            field_list: field_list
+                .iter()
+                .map(|val| TypeField {
+                    name: val.0.to_string(),
+                    field_type: val.1.to_string(),
+                    optional: false,
+                    desc: "".to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    pub fn test_build_field_list() {
+        let type_store = TypeStore::new(&[
+            make_type("", "UserInfo", &[("Name", "String"), ("Address", "Option<Address>")]),
+            make_type("", "Address", &[("City", "String")]),
+            make_type("", "TreeNode", &[("Value", "i32"), ("Children", "Vec<TreeNode>")]),
+        ]);
+        let field_list = type_store.build_field_list("Vec<UserInfo>", "user").unwrap();
+        assert_eq!(field_list[1].path, "user.Address");
+        assert_eq!(field_list[1].children[0].path, "user.Address.City");
+        assert!(field_list[0].required);
+        assert!(type_store.build_field_list("String", "").is_none());
+
+        // 自引用的类型只展开一层
+        let field_list = type_store.build_field_list("TreeNode", "").unwrap();
+        assert_eq!(field_list[1].path, "Children");
+        assert!(field_list[1].children.is_empty());
+    }
+
+    #[test]
+    pub fn test_build_param_list() {
+        let type_store = TypeStore::new(&[
+            make_type("", "PageQuery", &[("Page", "u32"), ("Size", "u32")]),
+            make_type("", "Address", &[("City", "String")]),
+        ]);
+        let param_list = vec![
+            ApiParam {
+                param_type: "PageQuery".to_string(),
+                flatten: true,
+                location: Some(ParamLocation::Query),
+                ..ApiParam::default()
+            },
+            ApiParam {
+                name: "User".to_string(),
+                param_type: "object".to_string(),
+                children: vec![ApiParam {
+                    name: "Address".to_string(),
+                    param_type: "Address".to_string(),
+                    ..ApiParam::default()
+                }],
+                ..ApiParam::default()
+            },
+            ApiParam {
+                param_type: "Unknown".to_string(),
+                flatten: true,
+                ..ApiParam::default()
+            },
+        ];
+        let view_list = type_store.build_param_list(&param_list);
+        // param type {TypeName} 的字段直接做为参数，并使用其参数位置
+        assert_eq!(view_list[0].name, "Page");
+        assert_eq!(view_list[1].path, "Size");
+        assert_eq!(view_list[1].location, Some(ParamLocation::Query));
+        // 文档中的子参数以及类型的字段都使用完整路径
+        assert_eq!(view_list[2].children[0].path, "User.Address");
+        assert_eq!(view_list[2].children[0].children[0].path, "User.Address.City");
+        // 未文档化的类型保留原参数
+        assert_eq!(view_list.len(), 4);
+        assert_eq!(view_list[3].field_type, "Unknown");
+    }
+
+    #[test]
+    pub fn test_same_name_type() {
+        let type_store = TypeStore::new(&[
+            make_type("", "UserInfo", &[("Name", "String")]),
+            make_type("model::user", "UserInfo", &[("Id", "u64")]),
+        ]);
+        let field_list = type_store.build_field_list("Vec<UserInfo>", "").unwrap();
+        assert_eq!(field_list[0].name, "Name");
+        let field_list = type_store.build_field_list("crate::model::user::UserInfo", "").unwrap();
+        assert_eq!(field_list[0].name, "Id");
+        let field_list = type_store.build_field_list("model::user::UserInfo", "").unwrap();
+        assert_eq!(field_list[0].name, "Id");
+        let field_list = type_store.build_field_list("crate::UserInfo", "").unwrap();
+        assert_eq!(field_list[0].name, "Name");
+        // 路径找不到时按类型名查找
+        let field_list = type_store.build_field_list("other::UserInfo", "").unwrap();
+        assert_eq!(field_list[0].name, "Name");
+    }
+}
@@ -1,10 +1,12 @@
 mod api_doc;
+mod api_view;
 mod project;
 
 use once_cell::sync::Lazy;
 use tera::Tera;
 use warp::http::StatusCode;
 use warp::{Filter, Rejection};
+use std::collections::HashMap;
 use std::str::FromStr;
 
 static TERA: Lazy<Tera> = Lazy::new(|| {
@@ -18,7 +20,7 @@ static TERA: Lazy<Tera> = Lazy::new(|| {
 
     tera_obj.autoescape_on(vec!["html"]);
 
-    if tera_obj.get_template_names().count()<=0{
+    if tera_obj.get_template_names().count()==0{
         println!("have no template");
         std::process::exit(1);
     }
@@ -46,13 +48,13 @@ static TERA: Lazy<Tera> = Lazy::new(|| {
 async fn main() {
     let arg_list:Vec<String>= std::env::args().collect();
     let mut port=8011u16;
-    if arg_list.len()!=0&& &arg_list[1].to_lowercase()=="-p"{
+    if arg_list.len()>2&& &arg_list[1].to_lowercase()=="-p"{
         port=match u16::from_str( &arg_list[2]){
             Ok(val)=>{
                 val
             },
             Err(err)=>{
-                println!("convert '{}' to listen port error:{}",&arg_list[2],err.to_string());
+                println!("convert '{}' to listen port error:{}",&arg_list[2],err);
                 std::process::exit(1);
             }
         }
@@ -72,6 +74,8 @@ async fn main() {
             .and(warp::path!("api" / String))
             .and(warp::path::end())
             .and(warp::get())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::header::optional::<String>("accept-language"))
             .and_then(get_api_list))
         .or(
             warp::path("static").and(warp::fs::dir("./static/"))
@@ -81,14 +85,13 @@ async fn main() {
     warp::serve(api_filter).run(([0, 0, 0, 0], port)).await;
 }
 
-/// module Main
-/// fn GetProjectList / get 获取项目列表
+/// 获取项目列表
 /// return
 ///     一个html文档
-#[doc_macro::api]
+#[doc_macro::api(module = "Main", name = "GetProjectList", path = "/", method = "get")]
 pub async fn get_project_list() -> Result<Box<dyn warp::Reply>, Rejection> {
     let result = project::get_project_list();
-    return match result {
+    match result {
         Ok(val) => {
             let mut ctx = tera::Context::new();
             ctx.insert("project_list", &val);
@@ -105,34 +108,56 @@ pub async fn get_project_list() -> Result<Box<dyn warp::Reply>, Rejection> {
             err.to_string(),
             StatusCode::INTERNAL_SERVER_ERROR,
         ))),
-    };
+    }
 }
 
 /// module Main
 /// fn GetApiList /api/{project_id} get 获取指定项目的API列表
 /// param
-///     ProjectId    string required     项目Id ，此参数在路径上面
+///     ProjectId    string in:path required     项目Id
+///     Group        string in:query optional    分组方式，module：按模块分组，tag：按标签分组，默认为module
+///     Lang         string in:query optional    描述语言，如en、zh，为空时使用默认描述。未指定时按Accept-Language选择
+/// header
+///     Accept-Language    string optional    期望的描述语言
 /// return
 ///     一个html文档
 #[doc_macro::api]
-pub async fn get_api_list(project_id: String) -> Result<Box<dyn warp::Reply>, Rejection> {
-    let project_item;
-    match project::get_project_item(project_id) {
-        Ok(val) => {
-            project_item = val;
-        }
+pub async fn get_api_list(
+    project_id: String,
+    query: HashMap<String, String>,
+    accept_language: Option<String>,
+) -> Result<Box<dyn warp::Reply>, Rejection> {
+    let group_type = match query.get("group") {
+        Some(val) => match api_doc::GroupType::from_str(val) {
+            Ok(val) => val,
+            Err(err) => {
+                return Ok(Box::new(warp::reply::with_status(err, StatusCode::BAD_REQUEST)));
+            }
+        },
+        None => api_doc::GroupType::Module,
+    };
+
+    let project_item = match project::get_project_item(project_id) {
+        Ok(val) => val,
         Err(err) => {
             return Ok(Box::new(warp::reply::with_status(
                 err,
                 StatusCode::INTERNAL_SERVER_ERROR,
             )));
         }
-    }
+    };
 
-    return match api_doc::get_grouped_api_list(&project_item) {
+    let lang_candidate_list = match (query.get("lang"), accept_language) {
+        (Some(val), _) => vec![val.clone()],
+        (None, Some(val)) => api_doc::parse_accept_language(&val),
+        (None, None) => Vec::new(),
+    };
+
+    match api_doc::get_grouped_api_list(&project_item, group_type, &lang_candidate_list) {
         Ok(val) => {
             let mut ctx = tera::Context::new();
             ctx.insert("api_list", &val);
+            ctx.insert("group_type", &group_type);
             ctx.insert("project_item", &project_item);
 
             match TERA.render("api.html", &ctx) {
@@ -147,5 +172,5 @@ pub async fn get_api_list(project_id: String) -> Result<Box<dyn warp::Reply>, Re
             err,
             StatusCode::INTERNAL_SERVER_ERROR,
         ))),
-    };
+    }
 }
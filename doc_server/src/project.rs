@@ -12,20 +12,17 @@ pub struct ProjectInfo {
 }
 
 pub fn get_project_list() -> Result<Vec<ProjectInfo>, String> {
-    let content;
-    match fs::read_to_string("./project.json") {
-        Ok(val) => {
-            content = val;
-        }
+    let content = match fs::read_to_string("./project.json") {
+        Ok(val) => val,
         Err(err) => {
             return Err(err.to_string());
         }
-    }
+    };
 
-    return match serde_json::from_str::<Vec<ProjectInfo>>(&content) {
+    match serde_json::from_str::<Vec<ProjectInfo>>(&content) {
         Ok(val) => Ok(val),
         Err(err) => Err(err.to_string()),
-    };
+    }
 }
 
 pub fn get_project_item(project_id: String) -> Result<ProjectInfo, String> {
@@ -36,5 +33,5 @@ pub fn get_project_item(project_id: String) -> Result<ProjectInfo, String> {
         }
     }
 
-    return Err(format!("no found target project:{}", project_id));
+    Err(format!("no found target project:{}", project_id))
 }